@@ -0,0 +1,66 @@
+#!/bin/bash
+set -euo pipefail
+
+APP_ID="markzap"
+BINARY_NAME="markzap"
+SCRIPT_DIR="$(cd "$(dirname "$0")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+SOURCE_ICON="$PROJECT_DIR/assets/icons/logomarkzap.png"
+DESKTOP_FILE="$PROJECT_DIR/resources/linux/$APP_ID.desktop"
+
+# Install under ~/.local by default; `PREFIX=/usr/local sudo -E ...` for all users.
+PREFIX="${PREFIX:-$HOME/.local}"
+BIN_DIR="$PREFIX/bin"
+APPLICATIONS_DIR="$PREFIX/share/applications"
+ICONS_DIR="$PREFIX/share/icons/hicolor"
+
+ICON_SIZES=(16 24 32 48 64 128 256 512)
+
+if [[ "${1:-}" == "--uninstall" ]]; then
+    rm -f "$BIN_DIR/$BINARY_NAME" "$APPLICATIONS_DIR/$APP_ID.desktop"
+    for SIZE in "${ICON_SIZES[@]}"; do
+        rm -f "$ICONS_DIR/${SIZE}x${SIZE}/apps/$APP_ID.png"
+    done
+    update-desktop-database "$APPLICATIONS_DIR" 2>/dev/null || true
+    gtk-update-icon-cache -q -t "$ICONS_DIR" 2>/dev/null || true
+    echo "MarkZap has been uninstalled from $PREFIX."
+    exit 0
+fi
+
+echo "Building $BINARY_NAME (release)..."
+cargo build --release --manifest-path "$PROJECT_DIR/Cargo.toml"
+
+echo "Installing binary to $BIN_DIR..."
+install -Dm755 "$PROJECT_DIR/target/release/$BINARY_NAME" "$BIN_DIR/$BINARY_NAME"
+
+echo "Installing desktop entry to $APPLICATIONS_DIR..."
+mkdir -p "$APPLICATIONS_DIR"
+# Point Exec at the installed binary, which may not be on the launcher's PATH.
+sed "s|^Exec=$BINARY_NAME |Exec=$BIN_DIR/$BINARY_NAME |" "$DESKTOP_FILE" > "$APPLICATIONS_DIR/$APP_ID.desktop"
+
+echo "Installing icons to $ICONS_DIR..."
+if command -v magick > /dev/null; then
+    RESIZE=(magick)
+elif command -v convert > /dev/null; then
+    RESIZE=(convert)
+else
+    RESIZE=()
+    echo "ImageMagick not found: installing the source icon at 512x512 only."
+fi
+for SIZE in "${ICON_SIZES[@]}"; do
+    TARGET="$ICONS_DIR/${SIZE}x${SIZE}/apps/$APP_ID.png"
+    mkdir -p "$(dirname "$TARGET")"
+    if [[ ${#RESIZE[@]} -gt 0 ]]; then
+        "${RESIZE[@]}" "$SOURCE_ICON" -resize "${SIZE}x${SIZE}" "$TARGET"
+    elif [[ "$SIZE" == 512 ]]; then
+        cp "$SOURCE_ICON" "$TARGET"
+    fi
+done
+
+# Refresh the caches so launchers pick up the entry, MIME types and icons.
+update-desktop-database "$APPLICATIONS_DIR" 2>/dev/null || true
+gtk-update-icon-cache -q -t "$ICONS_DIR" 2>/dev/null || true
+
+echo ""
+echo "MarkZap is now installed."
+echo "To set as default for .md files: xdg-mime default $APP_ID.desktop text/markdown"
@@ -1,22 +1,59 @@
 use std::fs;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use futures::StreamExt as _;
+use futures::channel::mpsc;
 use gpui::prelude::FluentBuilder;
 use gpui::{self, *};
-use gpui_component::ActiveTheme as _;
-use gpui_component::button::Button;
-use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::highlighter::{Diagnostic as EditorDiagnostic, DiagnosticSeverity};
+use gpui_component::input::{Input, InputEvent, InputState, Position};
 use gpui_component::switch::Switch;
 use gpui_component::webview::WebView;
+use gpui_component::{ActiveTheme as _, Theme};
 use gpui_component::{Icon, IconName, Sizable as _};
 use gpui_component::{h_flex, v_flex};
 
+use crate::appearance;
 use crate::assets::AppIconName;
-use crate::render;
+use crate::documents::{self, DocumentRegistry};
+use crate::editing::{self, Cursor, Edit};
+use crate::find;
+use crate::format;
+use crate::i18n;
+use crate::images;
+use crate::links::{self, BrokenLink};
+use crate::lint::{self, Diagnostic};
+use crate::lists;
+use crate::markup::{self, Inline};
+use crate::menus;
+use crate::open_requests;
+use crate::outline::{self, Heading};
+use crate::render::{self, PageOptions, PreviewMessage};
+use crate::session::{RecentFiles, SessionWindow};
+use crate::settings::Settings;
 use crate::slidev;
 use crate::state::AppMode;
-use crate::views::presentation;
+use crate::table;
+use crate::themes::PreviewTheme;
+use crate::views::command_palette::{self, Command};
+use crate::views::find_panel::{FindPanel, FindPanelEvent};
+use crate::views::picker::{Picker, PickerEvent, PickerItem};
+use crate::views::{menu_bar, presentation, quick_open, search, workspace};
+use crate::{
+    AddTableColumn, AddTableRow, CheckLinks, CommandPalette, CycleHeading, ExportHtml, Find,
+    FormatDocument, GoToHeading, Indent, InsertLink, Newline, Outdent, PasteMarkdown, QuickOpen,
+    RemoveTableColumn, RemoveTableRow, ResetZoom, SearchInFolder, SortTable, StartPresentation,
+    ToggleBold, ToggleCode, ToggleCodeBlock, ToggleItalic, ToggleMode, ToggleProblems, ToggleQuote,
+    ToggleTask, ZoomIn, ZoomOut,
+};
+
+/// Zoom factor applied per Zoom In / Zoom Out, and its limits.
+const ZOOM_STEP: f32 = 1.1;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.;
 
 pub struct AppView {
     mode: AppMode,
@@ -28,11 +65,66 @@ pub struct AppView {
     _subscription: Option<Subscription>,
     save_debounce: u64,
     preview_webview: Option<Entity<WebView>>,
+    /// Last scroll offset reported by the preview page.
+    scroll_y: f64,
+    /// Last known placement of the window, kept for session restore.
+    window_bounds: Option<WindowBounds>,
+    _window_subscriptions: Vec<Subscription>,
+    _settings_subscription: Subscription,
+    _theme_subscription: Subscription,
+    focus_handle: FocusHandle,
+    find_panel: Option<Entity<FindPanel>>,
+    /// Byte ranges of the find panel matches in `content`, and the selected one.
+    find_matches: Vec<Range<usize>>,
+    current_match: usize,
+    _find_subscription: Option<Subscription>,
+    /// Text scale of the editor and preview.
+    zoom: f32,
+    /// Overlay picker (quick open, command palette, headings) and what its items stand for.
+    picker: Option<(Entity<Picker>, PickerTarget)>,
+    _picker_subscription: Option<Subscription>,
+    /// False while another tab of the window is shown.
+    visible: bool,
+    /// Lint problems of `content`, shown as editor squiggles and in the
+    /// problems panel.
+    diagnostics: Vec<Diagnostic>,
+    lint_generation: u64,
+    show_problems: bool,
+    /// Result of the last Check Links, shown in the problems panel.
+    broken_links: Option<Vec<BrokenLink>>,
+    checking_links: bool,
+}
+
+/// What the items of the open picker stand for, in the same order.
+enum PickerTarget {
+    Files(Vec<PathBuf>),
+    Headings(Vec<Heading>),
+    Commands(Vec<Command>),
 }
 
 impl AppView {
-    pub fn new(content: String, file_path: Option<PathBuf>) -> Self {
+    pub fn new(content: String, file_path: Option<PathBuf>, cx: &mut Context<Self>) -> Self {
+        // A second view of an already open file shares its buffer rather than
+        // the (possibly stale) content on disk.
+        let content = file_path
+            .as_ref()
+            .and_then(|path| DocumentRegistry::views(path, cx).into_iter().next())
+            .map(|peer| peer.read(cx).content.clone())
+            .unwrap_or(content);
         let has_presentation = slidev::detect_presentation(&content);
+        let settings_subscription = cx.observe_global::<Settings>(|this, cx| {
+            // The content width lives in the page, the editor font in render().
+            if this.mode == AppMode::Preview {
+                this.refresh_preview(cx);
+            }
+            // The lint rules may have changed.
+            this.lint(cx);
+            cx.notify();
+        });
+        let theme_subscription = cx.observe_global::<Theme>(|this, cx| {
+            this.retheme_preview(cx);
+            cx.notify();
+        });
         Self {
             mode: AppMode::Preview,
             content,
@@ -43,9 +135,270 @@ impl AppView {
             _subscription: None,
             save_debounce: 0,
             preview_webview: None,
+            scroll_y: 0.,
+            window_bounds: None,
+            _window_subscriptions: Vec::new(),
+            _settings_subscription: settings_subscription,
+            _theme_subscription: theme_subscription,
+            focus_handle: cx.focus_handle(),
+            find_panel: None,
+            find_matches: Vec::new(),
+            current_match: 0,
+            _find_subscription: None,
+            zoom: 1.,
+            picker: None,
+            _picker_subscription: None,
+            visible: true,
+            diagnostics: Vec::new(),
+            lint_generation: 0,
+            show_problems: false,
+            broken_links: None,
+            checking_links: false,
+        }
+    }
+
+    /// Track the window placement so the session can restore it, and the OS
+    /// appearance so the theme follows it live.
+    pub fn observe_window(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.window_bounds = Some(window.window_bounds());
+        self._window_subscriptions = vec![
+            cx.observe_window_bounds(window, |this, window, _| {
+                this.window_bounds = Some(window.window_bounds());
+            }),
+            cx.observe_window_appearance(window, |_, window, cx| {
+                appearance::apply(Some(window), cx);
+            }),
+        ];
+    }
+
+    /// Reapply the mode and scroll position saved in a previous session.
+    /// Must be called before the first render.
+    pub fn restore(&mut self, saved: &SessionWindow) {
+        self.mode = saved.mode;
+        self.scroll_y = saved.scroll_y;
+    }
+
+    /// Window title for this document.
+    pub fn title(&self) -> String {
+        match self.file_path.as_ref().and_then(|p| p.file_name()) {
+            Some(name) => format!("MarkZap — {}", name.to_string_lossy()),
+            None => "MarkZap".to_string(),
+        }
+    }
+
+    /// Short name shown on the document's tab.
+    pub fn tab_label(&self) -> String {
+        match self.file_path.as_ref().and_then(|p| p.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => "MarkZap".to_string(),
+        }
+    }
+
+    /// Whether edits are waiting for the debounced save.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Show or hide this document when its tab is (de)activated. The native
+    /// preview WebView is drawn over the window, so it must be hidden too.
+    pub fn set_visible(&mut self, visible: bool, cx: &mut Context<Self>) {
+        self.visible = visible;
+        if let Some(ref wv) = self.preview_webview {
+            if visible && self.mode == AppMode::Preview && self.picker.is_none() {
+                wv.update(cx, |w, _| w.show());
+            } else {
+                wv.update(cx, |w, _| w.hide());
+            }
+        }
+    }
+
+    /// Switch between the preview and the editor.
+    pub fn set_mode(&mut self, mode: AppMode, cx: &mut Context<Self>) {
+        if mode == self.mode {
+            return;
+        }
+        match mode {
+            AppMode::Edit => {
+                // Hide the native WebView so it does not overlay the editor.
+                if let Some(ref wv) = self.preview_webview {
+                    wv.update(cx, |w, _| w.hide());
+                }
+                self.mode = AppMode::Edit;
+            }
+            AppMode::Preview => {
+                self.sync_content_from_editor(cx);
+                self.mode = AppMode::Preview;
+                self.refresh_preview(cx);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Show zero-based `line` in the editor, with the cursor at its start.
+    pub fn go_to_line(&mut self, line: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_mode(AppMode::Edit, cx);
+        let editor = self.ensure_editor(window, cx);
+        editor.update(cx, |state, cx| {
+            state.set_cursor_position(Position::new(line as u32, 0), window, cx);
+        });
+        window.focus(&editor.focus_handle(cx));
+    }
+
+    /// Search the folder of this document.
+    fn search_in_folder(&mut self, _: &SearchInFolder, _: &mut Window, cx: &mut Context<Self>) {
+        let root = self
+            .file_path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
+        search::open_search_window(root, cx);
+    }
+
+    /// Open files and folders dropped on the window. Markdown files replace
+    /// the welcome screen, or open in new windows; a folder opens the
+    /// quick-open picker over its Markdown files, to pick one to open.
+    fn open_dropped(&mut self, paths: &[PathBuf], window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(folder) = paths.iter().find(|path| path.is_dir()) {
+            let recent = quick_open::recent_files(self.file_path.as_deref(), cx)
+                .into_iter()
+                .filter(|path| path.starts_with(folder))
+                .collect();
+            self.open_quick_open(Some(folder.clone()), recent, window, cx);
+        }
+        for path in paths {
+            if !crate::search::is_markdown(path) || path.is_dir() {
+                continue;
+            } else if self.file_path.is_none() {
+                self.open_here(path, window, cx);
+            } else {
+                open_requests::send(path.clone(), cx);
+            }
         }
     }
 
+    /// Show the document at `path` in this view, in place of the welcome
+    /// screen, or focus the view that already shows it.
+    fn open_here(&mut self, path: &Path, window: &mut Window, cx: &mut Context<Self>) {
+        let path = documents::canonical_path(path);
+        if let Some((window, view)) = DocumentRegistry::focus(&path, cx) {
+            workspace::reveal(window, &view, cx);
+            return;
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Error reading file {:?}: {}", path, e);
+                return;
+            }
+        };
+        RecentFiles::add(&path, cx);
+        DocumentRegistry::register(&path, window.window_handle(), &cx.entity(), cx);
+        self.file_path = Some(path);
+        self.adopt_content(content, cx);
+        window.set_window_title(&self.title());
+    }
+
+    /// Snapshot of this view for the session file, if it shows a file.
+    pub fn session_window(&self) -> Option<SessionWindow> {
+        Some(SessionWindow {
+            path: self.file_path.clone()?,
+            mode: self.mode,
+            scroll_y: self.scroll_y,
+            bounds: self.window_bounds.map(Into::into),
+        })
+    }
+
+    fn page_options(&self, cx: &App) -> PageOptions {
+        let dark = cx.theme().is_dark();
+        let settings = Settings::get(cx);
+        let theme = PreviewTheme::from_id(&settings.preview_theme);
+        PageOptions {
+            dark,
+            palette: theme.palette(dark),
+            custom_css: theme.custom_css(),
+            scroll_y: self.scroll_y,
+            content_width: settings.content_width,
+            zoom: self.zoom,
+            strings: i18n::tr(cx),
+            editable_tasks: true,
+        }
+    }
+
+    /// Write the rendered page, styled with the current preview theme, to an
+    /// HTML file chosen by the user.
+    fn export_html(&mut self, _: &ExportHtml, _window: &mut Window, cx: &mut Context<Self>) {
+        let page = PageOptions {
+            scroll_y: 0.,
+            zoom: 1.,
+            editable_tasks: false,
+            ..self.page_options(cx)
+        };
+        let html = render::render_markdown_page(&self.content, page);
+
+        let directory = self
+            .file_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(Path::to_path_buf)
+            .or_else(dirs::home_dir)
+            .unwrap_or_default();
+        let suggested_name = self
+            .file_path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| format!("{}.html", stem.to_string_lossy()))
+            .unwrap_or_else(|| "document.html".to_string());
+        let receiver = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+        cx.spawn(async move |_, _| {
+            if let Ok(Ok(Some(path))) = receiver.await {
+                if let Err(e) = fs::write(&path, html) {
+                    eprintln!("Error exporting {:?}: {}", path, e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    fn handle_preview_message(
+        &mut self,
+        message: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match PreviewMessage::parse(message) {
+            Some(PreviewMessage::Scroll { y }) => self.scroll_y = y,
+            Some(PreviewMessage::Task { line }) => self.toggle_preview_task(line, window, cx),
+            None => eprintln!("Unknown preview message: {}", message),
+        }
+    }
+
+    /// Write a checkbox clicked in the preview back to the task item on
+    /// one-based `line`, as an edit of the editor text that can be undone.
+    fn toggle_preview_task(&mut self, line: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let editor = self.ensure_editor(window, cx);
+        let (text, position) = {
+            let state = editor.read(cx);
+            (state.value().to_string(), state.cursor_position())
+        };
+        let lines = editing::lines(&text);
+        let toggled = line
+            .checked_sub(1)
+            .and_then(|ix| lines.get(ix))
+            .and_then(|text| lists::toggle_task_line(text));
+        let Some(toggled) = toggled else {
+            // The page no longer matches the document: show it as it is.
+            self.refresh_preview(cx);
+            return;
+        };
+        let start: usize = lines[..line - 1].iter().map(|line| line.len() + 1).sum();
+        let (range, mark) = editing::changed_range(&lines[line - 1], &toggled);
+        self.replace_in_editor(start + range.start..start + range.end, mark, window, cx);
+        // The page already shows the new state; only the cursor moved.
+        editor.update(cx, |state, cx| {
+            state.set_cursor_position(position, window, cx)
+        });
+    }
+
     /// Lazily create the editor state on first switch to Edit mode.
     fn ensure_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Entity<InputState> {
         if let Some(ref state) = self.editor_state {
@@ -65,28 +418,7 @@ impl AppView {
                 if let InputEvent::Change = event {
                     if let Some(ref state) = this.editor_state {
                         this.content = state.read(cx).value().to_string();
-                        this.has_presentation = slidev::detect_presentation(&this.content);
-                        cx.notify();
-
-                        // Debounce save: increment generation counter and schedule a save
-                        this.dirty = true;
-                        this.save_debounce += 1;
-                        let generation = this.save_debounce;
-                        let entity = cx.entity().clone();
-                        cx.spawn(async move |_, cx| {
-                            cx.background_executor().timer(Duration::from_secs(2)).await;
-                            cx.update(|cx| {
-                                entity.update(cx, |this, cx| {
-                                    if this.save_debounce == generation {
-                                        this.save_to_file();
-                                        this.dirty = false;
-                                        cx.notify();
-                                    }
-                                });
-                            })
-                            .ok();
-                        })
-                        .detach();
+                        this.content_changed(cx);
                     }
                 }
             },
@@ -94,9 +426,929 @@ impl AppView {
 
         self.editor_state = Some(state.clone());
         self._subscription = Some(subscription);
+        self.lint(cx);
         state
     }
 
+    /// Lint the content in the background, then refresh the squiggles and
+    /// the problems panel. Skipped while neither is shown.
+    fn lint(&mut self, cx: &mut Context<Self>) {
+        let config = Settings::get(cx).lint.clone();
+        if !config.enabled || (self.editor_state.is_none() && !self.show_problems) {
+            if !self.diagnostics.is_empty() {
+                self.diagnostics.clear();
+                self.update_editor_diagnostics(cx);
+            }
+            return;
+        }
+        self.lint_generation += 1;
+        let generation = self.lint_generation;
+        let content = self.content.clone();
+        cx.spawn(async move |this, cx| {
+            let diagnostics = cx
+                .background_spawn(async move { lint::lint(&content, &config) })
+                .await;
+            this.update(cx, |this, cx| {
+                // Drop results for content edited since.
+                if this.lint_generation == generation {
+                    this.diagnostics = diagnostics;
+                    this.update_editor_diagnostics(cx);
+                    cx.notify();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Show `self.diagnostics` as squiggles in the editor.
+    fn update_editor_diagnostics(&self, cx: &mut Context<Self>) {
+        let Some(ref editor) = self.editor_state else {
+            return;
+        };
+        editor.update(cx, |state, cx| {
+            let text = state.text().clone();
+            let Some(set) = state.diagnostics_mut() else {
+                return;
+            };
+            set.reset(&text);
+            for diagnostic in &self.diagnostics {
+                let line = diagnostic.line as u32;
+                let range = Position::new(line, diagnostic.start_column as u32)
+                    ..Position::new(line, diagnostic.end_column as u32);
+                set.push(
+                    EditorDiagnostic::new(range, diagnostic.message.clone())
+                        .with_severity(DiagnosticSeverity::Warning),
+                );
+            }
+            cx.notify();
+        });
+    }
+
+    fn toggle_problems(&mut self, _: &ToggleProblems, _: &mut Window, cx: &mut Context<Self>) {
+        self.show_problems = !self.show_problems;
+        self.lint(cx);
+        cx.notify();
+    }
+
+    fn check_links(&mut self, _: &CheckLinks, _: &mut Window, cx: &mut Context<Self>) {
+        self.show_problems = true;
+        self.lint(cx);
+        self.checking_links = true;
+        let content = self.content.clone();
+        let base_dir = self
+            .file_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(Path::to_path_buf);
+        let client = Settings::get(cx)
+            .check_external_links
+            .then(|| cx.http_client());
+        cx.spawn(async move |this, cx| {
+            let broken = cx
+                .background_spawn(async move {
+                    links::check(&content, base_dir.as_deref(), client).await
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.broken_links = Some(broken);
+                this.checking_links = false;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    /// A row of the problems panel, jumping to zero-based `line` on click.
+    fn problem_row(
+        &self,
+        id: (&'static str, usize),
+        location: String,
+        message: String,
+        detail: &'static str,
+        line: usize,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let hover = cx.theme().accent;
+        h_flex()
+            .id(id)
+            .px_2()
+            .gap_2()
+            .text_xs()
+            .rounded_md()
+            .cursor_pointer()
+            .hover(move |style| style.bg(hover))
+            .on_click(cx.listener(move |this, _, window, cx| this.go_to_line(line, window, cx)))
+            .child(
+                div()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(location),
+            )
+            .child(div().flex_1().child(message))
+            .child(div().text_color(cx.theme().muted_foreground).child(detail))
+            .into_any_element()
+    }
+
+    /// The problems panel: lint diagnostics and the last link check, each
+    /// row jumping to its line.
+    fn render_problems(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let t = i18n::tr(cx);
+        let muted = cx.theme().muted_foreground;
+        let status = |text: &'static str| div().px_2().text_xs().text_color(muted).child(text);
+        let heading = |text: String| {
+            div()
+                .text_xs()
+                .font_weight(FontWeight::SEMIBOLD)
+                .child(text)
+        };
+
+        let mut rows: Vec<AnyElement> = vec![
+            heading(format!("{} ({})", t.menu_problems, self.diagnostics.len())).into_any_element(),
+        ];
+        if self.diagnostics.is_empty() {
+            rows.push(status(t.problems_none).into_any_element());
+        }
+        for (ix, diagnostic) in self.diagnostics.iter().enumerate() {
+            rows.push(self.problem_row(
+                ("problem", ix),
+                format!("{}:{}", diagnostic.line + 1, diagnostic.start_column + 1),
+                diagnostic.message.clone(),
+                diagnostic.rule.id(),
+                diagnostic.line,
+                cx,
+            ));
+        }
+
+        if self.checking_links || self.broken_links.is_some() {
+            rows.push(heading(t.menu_check_links.to_string()).into_any_element());
+        }
+        match self.broken_links {
+            _ if self.checking_links => rows.push(status(t.links_checking).into_any_element()),
+            None => {}
+            Some(ref broken) if broken.is_empty() => {
+                rows.push(status(t.links_none).into_any_element())
+            }
+            Some(ref broken) => {
+                for (ix, broken) in broken.iter().enumerate() {
+                    rows.push(self.problem_row(
+                        ("broken-link", ix),
+                        (broken.link.line + 1).to_string(),
+                        broken.description(),
+                        "",
+                        broken.link.line,
+                        cx,
+                    ));
+                }
+            }
+        }
+
+        div()
+            .id("problems")
+            .flex_none()
+            .max_h(px(160.))
+            .p_2()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .overflow_y_scroll()
+            .child(v_flex().gap_1().children(rows))
+    }
+
+    /// React to an edit of `self.content`: update peers and find results,
+    /// and schedule a debounced save.
+    fn content_changed(&mut self, cx: &mut Context<Self>) {
+        self.has_presentation = slidev::detect_presentation(&self.content);
+        self.sync_peers(cx);
+        self.lint(cx);
+        if self.find_panel.is_some() {
+            self.search(cx);
+        }
+        cx.notify();
+
+        // Debounce save: increment generation counter and schedule a save
+        self.dirty = true;
+        self.save_debounce += 1;
+        let generation = self.save_debounce;
+        let delay = Settings::get(cx).autosave_delay();
+        let entity = cx.entity().clone();
+        cx.spawn(async move |_, cx| {
+            cx.background_executor().timer(delay).await;
+            cx.update(|cx| {
+                entity.update(cx, |this, cx| {
+                    if this.save_debounce == generation {
+                        this.save_to_file();
+                        this.dirty = false;
+                        cx.notify();
+                    }
+                });
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Replace the editor text programmatically (find/replace and Format
+    /// Document), going through the normal save path.
+    fn set_editor_content(&mut self, content: String, window: &mut Window, cx: &mut Context<Self>) {
+        let editor = self.ensure_editor(window, cx);
+        let (range, text) = {
+            let old = editor.read(cx).value();
+            let (range, text) = editing::changed_range(&old, &content);
+            (range, text.to_string())
+        };
+        self.replace_in_editor(range, &text, window, cx);
+        if self.mode == AppMode::Preview {
+            self.refresh_preview(cx);
+        }
+    }
+
+    /// Replace the bytes `range` of the editor text with `text`, as one step
+    /// the editor can undo. Its change event runs `content_changed`.
+    fn replace_in_editor(
+        &mut self,
+        range: Range<usize>,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let editor = self.ensure_editor(window, cx);
+        editor.update(cx, |state, cx| {
+            let value = state.value();
+            let range =
+                editing::utf16_index(&value, range.start)..editing::utf16_index(&value, range.end);
+            state.replace_text_in_range(Some(range), text, window, cx);
+        });
+        self.content = editor.read(cx).value().to_string();
+    }
+
+    /// Apply a Markdown editing command at the editor cursor. Returns false,
+    /// leaving the text alone, when the command does not apply there.
+    fn apply_edit(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        command: impl FnOnce(&str, Cursor) -> Option<Edit>,
+    ) -> bool {
+        let Some(editor) = self.editor_state.clone() else {
+            return false;
+        };
+        let (text, position) = {
+            let state = editor.read(cx);
+            (state.value().to_string(), state.cursor_position())
+        };
+        let cursor = Cursor {
+            line: position.line as usize,
+            column: position.character as usize,
+        };
+        let Some(edit) = command(&text, cursor) else {
+            return false;
+        };
+        self.replace_in_editor(edit.range, &edit.text, window, cx);
+        let position = Position::new(edit.cursor.line as u32, edit.cursor.column as u32);
+        editor.update(cx, |state, cx| {
+            state.set_cursor_position(position, window, cx);
+        });
+        true
+    }
+
+    /// Apply a formatting command to the editor selection (byte offsets of
+    /// the text) and give the editor its focus back from the toolbar.
+    fn apply_format(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        command: impl FnOnce(&str, Range<usize>) -> Option<Edit>,
+    ) {
+        if self.mode != AppMode::Edit {
+            return;
+        }
+        let Some(editor) = self.editor_state.clone() else {
+            return;
+        };
+        let Some(selection) =
+            editor.update(cx, |state, cx| state.selected_text_range(true, window, cx))
+        else {
+            return;
+        };
+        let selection = {
+            let text = editor.read(cx).value();
+            editing::byte_index_utf16(&text, selection.range.start)
+                ..editing::byte_index_utf16(&text, selection.range.end)
+        };
+        self.apply_edit(window, cx, |text, _| command(text, selection));
+        window.focus(&editor.focus_handle(cx));
+    }
+
+    fn toggle_bold(&mut self, _: &ToggleBold, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_format(window, cx, |text, selection| {
+            Some(markup::toggle_inline(text, selection, Inline::Bold))
+        });
+    }
+
+    fn toggle_italic(&mut self, _: &ToggleItalic, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_format(window, cx, |text, selection| {
+            Some(markup::toggle_inline(text, selection, Inline::Italic))
+        });
+    }
+
+    fn toggle_code(&mut self, _: &ToggleCode, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_format(window, cx, |text, selection| {
+            Some(markup::toggle_inline(text, selection, Inline::Code))
+        });
+    }
+
+    fn insert_link(&mut self, _: &InsertLink, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_format(window, cx, |text, selection| {
+            Some(markup::link(text, selection))
+        });
+    }
+
+    fn cycle_heading(&mut self, _: &CycleHeading, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_format(window, cx, markup::cycle_heading);
+    }
+
+    fn toggle_quote(&mut self, _: &ToggleQuote, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_format(window, cx, markup::toggle_quote);
+    }
+
+    fn toggle_code_block(
+        &mut self,
+        _: &ToggleCodeBlock,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.apply_format(window, cx, |text, selection| {
+            Some(markup::toggle_code_block(text, selection))
+        });
+    }
+
+    /// The optional row of formatting buttons above the editor.
+    fn render_format_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let t = i18n::tr(cx);
+        let button = |id: &'static str, label: &'static str, tooltip: &'static str| {
+            Button::new(id)
+                .label(label)
+                .small()
+                .ghost()
+                .tooltip(tooltip)
+        };
+        h_flex()
+            .w_full()
+            .px_4()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(button("format-bold", "B", t.menu_bold).on_click(
+                cx.listener(|this, _, window, cx| this.toggle_bold(&ToggleBold, window, cx)),
+            ))
+            .child(button("format-italic", "I", t.menu_italic).on_click(
+                cx.listener(|this, _, window, cx| this.toggle_italic(&ToggleItalic, window, cx)),
+            ))
+            .child(button("format-code", "`", t.menu_code).on_click(
+                cx.listener(|this, _, window, cx| this.toggle_code(&ToggleCode, window, cx)),
+            ))
+            .child(button("format-link", "[]()", t.menu_link).on_click(
+                cx.listener(|this, _, window, cx| this.insert_link(&InsertLink, window, cx)),
+            ))
+            .child(button("format-heading", "H", t.menu_heading).on_click(
+                cx.listener(|this, _, window, cx| this.cycle_heading(&CycleHeading, window, cx)),
+            ))
+            .child(button("format-quote", ">", t.menu_quote).on_click(
+                cx.listener(|this, _, window, cx| this.toggle_quote(&ToggleQuote, window, cx)),
+            ))
+            .child(
+                button("format-code-block", "```", t.menu_code_block).on_click(cx.listener(
+                    |this, _, window, cx| this.toggle_code_block(&ToggleCodeBlock, window, cx),
+                )),
+            )
+    }
+
+    /// Enter: continue the list or blockquote at the cursor (see `lists`).
+    /// Elsewhere the editor breaks the line as usual.
+    fn newline(&mut self, _: &Newline, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.apply_edit(window, cx, lists::newline) {
+            cx.propagate();
+        }
+    }
+
+    /// Tab: in a table, align it and move to the next cell; on a list item,
+    /// nest it under the previous one. Otherwise the editor indents as usual.
+    fn indent(&mut self, _: &Indent, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.apply_edit(window, cx, table::next_cell)
+            && !self.apply_edit(window, cx, lists::indent)
+        {
+            cx.propagate();
+        }
+    }
+
+    fn outdent(&mut self, _: &Outdent, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.apply_edit(window, cx, table::previous_cell)
+            && !self.apply_edit(window, cx, lists::outdent)
+        {
+            cx.propagate();
+        }
+    }
+
+    fn toggle_task(&mut self, _: &ToggleTask, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_edit(window, cx, lists::toggle_task);
+    }
+
+    /// Paste an image into the assets folder, referenced at the cursor, or
+    /// CSV/TSV text as a Markdown table; anything else is pasted by the
+    /// editor as usual.
+    fn paste_markdown(&mut self, _: &PasteMarkdown, window: &mut Window, cx: &mut Context<Self>) {
+        let item = cx.read_from_clipboard();
+        let image = item.as_ref().and_then(|item| {
+            item.entries().iter().find_map(|entry| match entry {
+                ClipboardEntry::Image(image) => Some(image),
+                _ => None,
+            })
+        });
+        if let (Some(image), Some(path)) = (image, self.file_path.as_ref()) {
+            match images::save(path, image.bytes(), images::extension(image.format)) {
+                Ok(reference) => {
+                    self.apply_edit(window, cx, |text, cursor| {
+                        Some(editing::insert(text, cursor, &reference))
+                    });
+                    return;
+                }
+                Err(e) => eprintln!("Error saving pasted image: {}", e),
+            }
+        }
+
+        let table = item
+            .and_then(|item| item.text())
+            .and_then(|text| table::from_delimited(&text));
+        let pasted = table.is_some_and(|table| {
+            self.apply_edit(window, cx, |text, cursor| {
+                let lines = editing::lines(text);
+                (!editing::in_code_block(&lines, cursor.line))
+                    .then(|| editing::insert_block(text, cursor, &table))
+            })
+        });
+        if !pasted {
+            cx.propagate();
+        }
+    }
+
+    /// Copy image files dropped on the editor into the assets folder and
+    /// reference them at the cursor.
+    fn drop_images(&mut self, paths: &ExternalPaths, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ref document) = self.file_path else {
+            return;
+        };
+        let references: Vec<String> = paths
+            .paths()
+            .iter()
+            .filter(|path| images::is_image(path))
+            .filter_map(|path| {
+                images::copy(document, path)
+                    .inspect_err(|e| eprintln!("Error copying image {:?}: {}", path, e))
+                    .ok()
+            })
+            .collect();
+        if references.is_empty() {
+            return;
+        }
+        cx.stop_propagation();
+        self.apply_edit(window, cx, |text, cursor| {
+            Some(editing::insert(text, cursor, &references.join("\n")))
+        });
+    }
+
+    fn add_table_row(&mut self, _: &AddTableRow, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_edit(window, cx, table::add_row);
+    }
+
+    fn remove_table_row(
+        &mut self,
+        _: &RemoveTableRow,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.apply_edit(window, cx, table::remove_row);
+    }
+
+    fn add_table_column(
+        &mut self,
+        _: &AddTableColumn,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.apply_edit(window, cx, table::add_column);
+    }
+
+    fn remove_table_column(
+        &mut self,
+        _: &RemoveTableColumn,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.apply_edit(window, cx, table::remove_column);
+    }
+
+    fn sort_table(&mut self, _: &SortTable, window: &mut Window, cx: &mut Context<Self>) {
+        self.apply_edit(window, cx, table::sort);
+    }
+
+    /// Reformat the document in the configured style (see `format`).
+    fn format_document(&mut self, _: &FormatDocument, window: &mut Window, cx: &mut Context<Self>) {
+        self.sync_content_from_editor(cx);
+        let formatted = format::format(&self.content, &Settings::get(cx).format);
+        if formatted != self.content {
+            self.set_editor_content(formatted, window, cx);
+        }
+    }
+
+    /// Open the find/replace panel (Edit mode) and focus its query field, or
+    /// the find bar of the preview page.
+    fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        if self.mode == AppMode::Preview {
+            if let Some(ref wv) = self.preview_webview {
+                let webview = wv.read(cx);
+                webview.focus().ok();
+                webview.evaluate_script(render::OPEN_FIND_SCRIPT).ok();
+            }
+            return;
+        }
+        let panel = match self.find_panel {
+            Some(ref panel) => panel.clone(),
+            None => {
+                let panel = cx.new(|cx| FindPanel::new(window, cx));
+                self._find_subscription =
+                    Some(cx.subscribe_in(&panel, window, Self::on_find_panel_event));
+                self.find_panel = Some(panel.clone());
+                panel
+            }
+        };
+        panel.read(cx).focus(window, cx);
+        cx.notify();
+    }
+
+    fn on_find_panel_event(
+        &mut self,
+        _panel: &Entity<FindPanel>,
+        event: &FindPanelEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let count = self.find_matches.len();
+        match event {
+            FindPanelEvent::Search => {
+                self.current_match = 0;
+                self.search(cx);
+                self.select_current_match(window, cx);
+            }
+            FindPanelEvent::Next if count > 0 => {
+                self.current_match = (self.current_match + 1) % count;
+                self.update_find_status(cx);
+                self.select_current_match(window, cx);
+            }
+            FindPanelEvent::Previous if count > 0 => {
+                self.current_match = (self.current_match + count - 1) % count;
+                self.update_find_status(cx);
+                self.select_current_match(window, cx);
+            }
+            FindPanelEvent::Replace => self.replace_current(window, cx),
+            FindPanelEvent::ReplaceAll => self.replace_all(window, cx),
+            FindPanelEvent::Close => {
+                self.find_panel = None;
+                self._find_subscription = None;
+                self.find_matches.clear();
+                if let Some(ref editor) = self.editor_state {
+                    window.focus(&editor.focus_handle(cx));
+                }
+                cx.notify();
+            }
+            FindPanelEvent::Next | FindPanelEvent::Previous => {}
+        }
+    }
+
+    /// Recompute the matches of the find panel query in `self.content`.
+    fn search(&mut self, cx: &mut Context<Self>) {
+        let Some(ref panel) = self.find_panel else {
+            return;
+        };
+        let (query, options) = {
+            let panel = panel.read(cx);
+            (panel.query(cx), panel.options())
+        };
+        match find::find_matches(&self.content, &query, options) {
+            Ok(matches) => {
+                self.find_matches = matches;
+                self.current_match = self
+                    .current_match
+                    .min(self.find_matches.len().saturating_sub(1));
+                self.update_find_status(cx);
+            }
+            Err(_) => {
+                self.find_matches.clear();
+                let status = i18n::tr(cx).find_invalid_regex;
+                panel.update(cx, |panel, cx| panel.set_status(status, cx));
+            }
+        }
+    }
+
+    fn update_find_status(&self, cx: &mut Context<Self>) {
+        let Some(ref panel) = self.find_panel else {
+            return;
+        };
+        let t = i18n::tr(cx);
+        let status = if self.find_matches.is_empty() {
+            if panel.read(cx).query(cx).is_empty() {
+                String::new()
+            } else {
+                t.find_no_results.to_string()
+            }
+        } else {
+            format!(
+                "{} {} {}",
+                self.current_match + 1,
+                t.find_of,
+                self.find_matches.len()
+            )
+        };
+        panel.update(cx, |panel, cx| panel.set_status(status, cx));
+    }
+
+    /// Move the editor cursor to the current match.
+    fn select_current_match(&self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(editor), Some(range)) = (
+            self.editor_state.as_ref(),
+            self.find_matches.get(self.current_match),
+        ) else {
+            return;
+        };
+        let (line, column) = find::line_column(&self.content, range.start);
+        editor.update(cx, |state, cx| {
+            state.set_cursor_position(Position::new(line as u32, column as u32), window, cx);
+        });
+    }
+
+    fn replace_current(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(panel), Some(range)) = (
+            self.find_panel.clone(),
+            self.find_matches.get(self.current_match).cloned(),
+        ) else {
+            return;
+        };
+        let panel = panel.read(cx);
+        let (query, replacement, options) =
+            (panel.query(cx), panel.replacement(cx), panel.options());
+        if let Ok(content) =
+            find::replace_one(&self.content, range.clone(), &query, &replacement, options)
+        {
+            // End of the inserted text, which may differ in length from the
+            // match (regex replacements expand capture groups).
+            let end = range.end + content.len() - self.content.len();
+            self.set_editor_content(content, window, cx);
+            // Move on to the first match after the replaced text, wrapping
+            // around to the first one.
+            self.search(cx);
+            self.current_match = self
+                .find_matches
+                .iter()
+                .position(|found| found.start >= end)
+                .unwrap_or(0);
+            self.update_find_status(cx);
+            self.select_current_match(window, cx);
+        }
+    }
+
+    fn replace_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(panel) = self.find_panel.clone() else {
+            return;
+        };
+        let panel = panel.read(cx);
+        let (query, replacement, options) =
+            (panel.query(cx), panel.replacement(cx), panel.options());
+        if let Ok((content, count)) =
+            find::replace_all(&self.content, &query, &replacement, options)
+        {
+            if count > 0 {
+                self.set_editor_content(content, window, cx);
+            }
+        }
+    }
+
+    /// Show the quick-open picker over recent files and the Markdown files of
+    /// this document's folder.
+    fn quick_open(&mut self, _: &QuickOpen, window: &mut Window, cx: &mut Context<Self>) {
+        let folder = self
+            .file_path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
+        let recent = quick_open::recent_files(self.file_path.as_deref(), cx);
+        self.open_quick_open(folder, recent, window, cx);
+    }
+
+    /// Show the quick-open picker over `paths` (recent files), then the
+    /// Markdown files under `folder` once scanned in the background.
+    fn open_quick_open(
+        &mut self,
+        folder: Option<PathBuf>,
+        paths: Vec<PathBuf>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let current = self.file_path.clone();
+        let items = quick_open::items(&paths, folder.as_deref());
+        let placeholder = i18n::tr(cx).quick_open_placeholder;
+        let picker = self.open_picker(items, placeholder, PickerTarget::Files(paths), window, cx);
+
+        let Some(folder) = folder else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let found = cx
+                .background_spawn({
+                    let folder = folder.clone();
+                    async move { crate::search::markdown_files(&folder) }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                // The picker may have been closed or replaced meanwhile.
+                let Some((open, PickerTarget::Files(paths))) = &mut this.picker else {
+                    return;
+                };
+                if *open != picker {
+                    return;
+                }
+                quick_open::merge(paths, found, current.as_deref());
+                let items = quick_open::items(paths, Some(&folder));
+                picker.update(cx, |picker, cx| picker.set_items(items, cx));
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn open_picker(
+        &mut self,
+        items: Vec<PickerItem>,
+        placeholder: &'static str,
+        target: PickerTarget,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Picker> {
+        let picker = cx.new(|cx| Picker::new(items, placeholder, window, cx));
+        self._picker_subscription = Some(cx.subscribe_in(&picker, window, Self::on_picker_event));
+        picker.read(cx).focus(window, cx);
+        // The native preview would be drawn over the overlay.
+        if let Some(ref wv) = self.preview_webview {
+            wv.update(cx, |w, _| w.hide());
+        }
+        self.picker = Some((picker.clone(), target));
+        cx.notify();
+        picker
+    }
+
+    fn close_picker(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.picker = None;
+        self._picker_subscription = None;
+        match (self.mode, &self.preview_webview, &self.editor_state) {
+            (AppMode::Preview, Some(wv), _) => {
+                wv.update(cx, |w, _| w.show());
+                window.focus(&self.focus_handle);
+            }
+            (AppMode::Edit, _, Some(editor)) => window.focus(&editor.focus_handle(cx)),
+            _ => window.focus(&self.focus_handle),
+        }
+        cx.notify();
+    }
+
+    fn on_picker_event(
+        &mut self,
+        _picker: &Entity<Picker>,
+        event: &PickerEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let target = self.picker.take().map(|(_, target)| target);
+        // Restore focus first, so commands reach this view.
+        self.close_picker(window, cx);
+        let (PickerEvent::Confirm(ix), Some(target)) = (*event, target) else {
+            return;
+        };
+        match target {
+            PickerTarget::Files(paths) => {
+                if let Some(path) = paths.get(ix) {
+                    open_requests::send(path.clone(), cx);
+                }
+            }
+            PickerTarget::Headings(headings) => {
+                if let Some(heading) = headings.get(ix) {
+                    self.reveal_heading(ix, heading.line, window, cx);
+                }
+            }
+            PickerTarget::Commands(commands) => {
+                if let Some(command) = commands.into_iter().nth(ix) {
+                    window.dispatch_action(command.action, cx);
+                }
+            }
+        }
+    }
+
+    /// Show the command palette over every action of this window.
+    fn command_palette(&mut self, _: &CommandPalette, window: &mut Window, cx: &mut Context<Self>) {
+        let commands = command_palette::commands(self.has_presentation, cx);
+        let items = command_palette::items(&commands, window);
+        let placeholder = i18n::tr(cx).command_palette_placeholder;
+        self.open_picker(
+            items,
+            placeholder,
+            PickerTarget::Commands(commands),
+            window,
+            cx,
+        );
+    }
+
+    /// List the headings of the document to jump to one.
+    fn go_to_heading(&mut self, _: &GoToHeading, window: &mut Window, cx: &mut Context<Self>) {
+        let headings = outline::headings(&self.content);
+        let items = headings
+            .iter()
+            .map(|heading| PickerItem {
+                label: format!(
+                    "{}{}",
+                    "  ".repeat(heading.level as usize - 1),
+                    heading.text
+                )
+                .into(),
+                detail: Some(format!("H{}", heading.level).into()),
+            })
+            .collect();
+        let placeholder = i18n::tr(cx).go_to_heading_placeholder;
+        self.open_picker(
+            items,
+            placeholder,
+            PickerTarget::Headings(headings),
+            window,
+            cx,
+        );
+    }
+
+    /// Scroll to the `index`-th heading, at zero-based `line` of the source.
+    fn reveal_heading(
+        &mut self,
+        index: usize,
+        line: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match self.mode {
+            AppMode::Edit => self.go_to_line(line, window, cx),
+            AppMode::Preview => {
+                if let Some(ref wv) = self.preview_webview {
+                    let script = render::scroll_to_heading_script(index);
+                    wv.read(cx).evaluate_script(&script).ok();
+                }
+            }
+        }
+    }
+
+    fn toggle_mode(&mut self, _: &ToggleMode, _: &mut Window, cx: &mut Context<Self>) {
+        let mode = match self.mode {
+            AppMode::Edit => AppMode::Preview,
+            AppMode::Preview => AppMode::Edit,
+        };
+        self.set_mode(mode, cx);
+    }
+
+    fn present(&mut self, _: &StartPresentation, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.has_presentation {
+            return;
+        }
+        let html = slidev::generate_presentation_html(&self.content, cx.theme().is_dark());
+        presentation::open_presentation_window(html, cx);
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_zoom(self.zoom * ZOOM_STEP, cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_zoom(self.zoom / ZOOM_STEP, cx);
+    }
+
+    fn reset_zoom(&mut self, _: &ResetZoom, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_zoom(1., cx);
+    }
+
+    /// Scale the editor and preview text of this window.
+    fn set_zoom(&mut self, zoom: f32, cx: &mut Context<Self>) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        if let Some(ref wv) = self.preview_webview {
+            wv.read(cx)
+                .evaluate_script(&render::zoom_script(self.zoom))
+                .ok();
+        }
+        cx.notify();
+    }
+
     /// Lazily create the preview WebView, loading the current content as HTML.
     fn ensure_preview_webview(
         &mut self,
@@ -107,11 +1359,52 @@ impl AppView {
             return wv.clone();
         }
 
-        let html = render::render_markdown_page(&self.content, cx.theme().is_dark());
+        let html = render::render_markdown_page(&self.content, self.page_options(cx));
+
+        // The page talks back through `window.ipc.postMessage`; the handler
+        // runs outside of gpui, so forward messages to a task on this view.
+        let (ipc_tx, mut ipc_rx) = mpsc::unbounded::<String>();
+        // Files dropped on the page come the same way, instead of the
+        // WebView navigating to them.
+        let (drop_tx, mut drop_rx) = mpsc::unbounded::<Vec<PathBuf>>();
         let wry_webview = wry::WebViewBuilder::new()
             .with_html(&html)
+            .with_ipc_handler(move |request| {
+                ipc_tx.unbounded_send(request.into_body()).ok();
+            })
+            .with_drag_drop_handler(move |event| {
+                if let wry::DragDropEvent::Drop { paths, .. } = event {
+                    drop_tx.unbounded_send(paths).ok();
+                }
+                true
+            })
             .build_as_child(window)
             .expect("Failed to create preview WebView");
+        cx.spawn_in(window, async move |this, cx| {
+            while let Some(message) = ipc_rx.next().await {
+                if this
+                    .update_in(cx, |this, window, cx| {
+                        this.handle_preview_message(&message, window, cx)
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        cx.spawn_in(window, async move |this, cx| {
+            while let Some(paths) = drop_rx.next().await {
+                if this
+                    .update_in(cx, |this, window, cx| this.open_dropped(&paths, window, cx))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        })
+        .detach();
 
         let entity = cx.new(|cx| WebView::new(wry_webview, window, cx));
         self.preview_webview = Some(entity.clone());
@@ -122,9 +1415,48 @@ impl AppView {
     /// preview WebView (if it exists), then make it visible.
     fn refresh_preview(&self, cx: &mut Context<Self>) {
         if let Some(ref wv) = self.preview_webview {
-            let html = render::render_markdown_page(&self.content, cx.theme().is_dark());
+            let html = render::render_markdown_page(&self.content, self.page_options(cx));
             wv.read(cx).load_html(&html).ok();
-            wv.update(cx, |w, _| w.show());
+            if self.visible {
+                wv.update(cx, |w, _| w.show());
+            }
+        }
+    }
+
+    /// Push the current content to the other views of the same file so they
+    /// all show one buffer. Only this view saves it.
+    fn sync_peers(&self, cx: &mut Context<Self>) {
+        let Some(ref path) = self.file_path else {
+            return;
+        };
+        let this = cx.entity_id();
+        for peer in DocumentRegistry::views(path, cx) {
+            if peer.entity_id() != this {
+                let content = self.content.clone();
+                peer.update(cx, |peer, cx| peer.adopt_content(content, cx));
+            }
+        }
+    }
+
+    /// Replace the buffer with content edited in another view of the same file.
+    fn adopt_content(&mut self, content: String, cx: &mut Context<Self>) {
+        self.content = content;
+        self.has_presentation = slidev::detect_presentation(&self.content);
+        // The editor is recreated from the new content on next render.
+        self.editor_state = None;
+        self._subscription = None;
+        self.lint(cx);
+        if self.mode == AppMode::Preview {
+            self.refresh_preview(cx);
+        }
+        cx.notify();
+    }
+
+    /// Apply the current theme to the preview without reloading it.
+    fn retheme_preview(&self, cx: &mut Context<Self>) {
+        if let Some(ref wv) = self.preview_webview {
+            let script = render::retheme_script(&self.page_options(cx));
+            wv.read(cx).evaluate_script(&script).ok();
         }
     }
 
@@ -148,6 +1480,12 @@ impl AppView {
     }
 }
 
+impl Focusable for AppView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
 impl Render for AppView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let is_edit = self.mode == AppMode::Edit;
@@ -157,15 +1495,24 @@ impl Render for AppView {
         // Build the content area depending on mode
         let content_area = if is_edit {
             let editor_state = self.ensure_editor(window, cx);
-            div()
+            v_flex()
                 .flex_1()
                 .size_full()
+                .children(self.find_panel.clone())
                 .child(
-                    Input::new(&editor_state)
-                        .h_full()
-                        .w_full()
-                        .font_family("Menlo")
-                        .text_sm(),
+                    // Markdown-aware editing keys are bound in this context.
+                    div()
+                        .key_context("MarkdownEditor")
+                        .flex_1()
+                        .min_h_0()
+                        .on_drop(cx.listener(Self::drop_images))
+                        .child(
+                            Input::new(&editor_state)
+                                .h_full()
+                                .w_full()
+                                .font_family(Settings::get(cx).editor_font_family.clone())
+                                .text_size(rems(0.875 * self.zoom)),
+                        ),
                 )
                 .into_any_element()
         } else {
@@ -177,11 +1524,48 @@ impl Render for AppView {
                 .into_any_element()
         };
 
-        // Clone content for the presentation closure
-        let content_for_presentation = self.content.clone();
-
         v_flex()
+            .relative()
             .size_full()
+            .track_focus(&self.focus_handle)
+            .key_context("AppView")
+            .on_drop(cx.listener(|this, paths: &ExternalPaths, window, cx| {
+                this.open_dropped(paths.paths(), window, cx)
+            }))
+            .on_action(cx.listener(Self::export_html))
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::search_in_folder))
+            .on_action(cx.listener(Self::quick_open))
+            .on_action(cx.listener(Self::command_palette))
+            .on_action(cx.listener(Self::go_to_heading))
+            .on_action(cx.listener(Self::toggle_mode))
+            .on_action(cx.listener(Self::present))
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::reset_zoom))
+            .on_action(cx.listener(Self::toggle_problems))
+            .on_action(cx.listener(Self::check_links))
+            .on_action(cx.listener(Self::format_document))
+            .on_action(cx.listener(Self::newline))
+            .on_action(cx.listener(Self::indent))
+            .on_action(cx.listener(Self::outdent))
+            .on_action(cx.listener(Self::paste_markdown))
+            .on_action(cx.listener(Self::toggle_task))
+            .on_action(cx.listener(Self::toggle_bold))
+            .on_action(cx.listener(Self::toggle_italic))
+            .on_action(cx.listener(Self::toggle_code))
+            .on_action(cx.listener(Self::insert_link))
+            .on_action(cx.listener(Self::cycle_heading))
+            .on_action(cx.listener(Self::toggle_quote))
+            .on_action(cx.listener(Self::toggle_code_block))
+            .on_action(cx.listener(Self::add_table_row))
+            .on_action(cx.listener(Self::remove_table_row))
+            .on_action(cx.listener(Self::add_table_column))
+            .on_action(cx.listener(Self::remove_table_column))
+            .on_action(cx.listener(Self::sort_table))
+            .when(!menus::has_global_menu(), |this| {
+                this.child(menu_bar::menu_bar(cx))
+            })
             // Top bar
             .child(
                 h_flex()
@@ -218,21 +1602,12 @@ impl Render for AppView {
                             .child(Switch::new("mode-switch").checked(is_edit).on_click({
                                 let view = cx.entity().clone();
                                 move |checked, _window, cx| {
-                                    view.update(cx, |this, cx| {
-                                        if *checked {
-                                            // Hide the native WebView so it does
-                                            // not overlay the editor.
-                                            if let Some(ref wv) = this.preview_webview {
-                                                wv.update(cx, |w, _| w.hide());
-                                            }
-                                            this.mode = AppMode::Edit;
-                                        } else {
-                                            this.sync_content_from_editor(cx);
-                                            this.mode = AppMode::Preview;
-                                            this.refresh_preview(cx);
-                                        }
-                                        cx.notify();
-                                    });
+                                    let mode = if *checked {
+                                        AppMode::Edit
+                                    } else {
+                                        AppMode::Preview
+                                    };
+                                    view.update(cx, |this, cx| this.set_mode(mode, cx));
                                 }
                             }))
                             .child(Icon::new(AppIconName::Pencil)),
@@ -246,19 +1621,30 @@ impl Render for AppView {
                                 this.child(
                                     Button::new("presentation-btn")
                                         .icon(AppIconName::Presentation)
-                                        .on_click({
-                                            let content = content_for_presentation.clone();
-                                            move |_ev, _window, cx| {
-                                                let html =
-                                                    slidev::generate_presentation_html(&content);
-                                                presentation::open_presentation_window(html, cx);
-                                            }
-                                        }),
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.present(&StartPresentation, window, cx)
+                                        })),
                                 )
                             }),
                     ),
             )
+            .when(is_edit && Settings::get(cx).format_toolbar, |this| {
+                this.child(self.render_format_toolbar(cx))
+            })
             // Content area
             .child(content_area)
+            .when(self.show_problems, |this| {
+                this.child(self.render_problems(cx))
+            })
+            .children(self.picker.as_ref().map(|(picker, _)| {
+                div()
+                    .absolute()
+                    .top(px(56.))
+                    .left_0()
+                    .right_0()
+                    .flex()
+                    .justify_center()
+                    .child(picker.clone())
+            }))
     }
 }
@@ -0,0 +1,51 @@
+use gpui::*;
+use gpui_component::{ActiveTheme as _, Theme, ThemeMode};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Strings;
+use crate::settings::Settings;
+
+/// Light/dark choice for the UI, previews and presentations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Appearance {
+    /// Follow the OS appearance, live.
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
+
+impl Appearance {
+    pub const ALL: [Self; 3] = [Self::Auto, Self::Light, Self::Dark];
+
+    pub fn label(self, strings: &Strings) -> &'static str {
+        match self {
+            Self::Auto => strings.appearance_auto,
+            Self::Light => strings.appearance_light,
+            Self::Dark => strings.appearance_dark,
+        }
+    }
+}
+
+/// Switch the gpui-component theme to match the appearance setting, or the
+/// OS appearance in auto mode. Views observe the `Theme` global and re-theme
+/// their previews and presentations in place.
+pub fn apply(window: Option<&mut Window>, cx: &mut App) {
+    let dark = match Settings::get(cx).appearance {
+        Appearance::Light => false,
+        Appearance::Dark => true,
+        Appearance::Auto => matches!(
+            cx.window_appearance(),
+            WindowAppearance::Dark | WindowAppearance::VibrantDark
+        ),
+    };
+    if cx.theme().is_dark() != dark {
+        let mode = if dark {
+            ThemeMode::Dark
+        } else {
+            ThemeMode::Light
+        };
+        Theme::change(mode, window, cx);
+    }
+}
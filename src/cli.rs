@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use gpui::http_client::HttpClient;
+
+use crate::format;
+use crate::http::SimpleHttpClient;
+use crate::links;
+use crate::lint::{self, Rule};
+use crate::search;
+use crate::settings::Settings;
+
+/// Exit status of a subcommand: success, problems found, or bad usage/IO.
+const EXIT_OK: i32 = 0;
+const EXIT_PROBLEMS: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+/// Run the headless subcommand (for CI) named by the first argument, if it
+/// is one, and return the process exit code. Anything else is left to the GUI.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        "lint" => Some(lint(rest)),
+        "links" => Some(check_links(rest)),
+        "fmt" => Some(fmt(rest)),
+        _ => None,
+    }
+}
+
+/// The Markdown files named by `paths`, directories searched recursively.
+fn markdown_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .flat_map(|path| {
+            if path.is_dir() {
+                search::markdown_files(path)
+            } else {
+                vec![path.clone()]
+            }
+        })
+        .collect()
+}
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .inspect_err(|e| eprintln!("Error reading file {:?}: {}", path, e))
+        .ok()
+}
+
+/// `markzap lint [--disable RULE]... PATH...`: print the problems of each
+/// file as `path:line:column: message [rule]`.
+fn lint(args: &[String]) -> i32 {
+    let mut config = Settings::load().lint;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--disable" => match args.next() {
+                Some(id) if Rule::ALL.iter().any(|rule| rule.id() == id) => {
+                    config.disabled_rules.push(id.clone())
+                }
+                _ => {
+                    let ids: Vec<&str> = Rule::ALL.iter().map(|rule| rule.id()).collect();
+                    eprintln!("--disable expects one of: {}", ids.join(", "));
+                    return EXIT_ERROR;
+                }
+            },
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("Usage: markzap lint [--disable RULE]... PATH...");
+        return EXIT_ERROR;
+    }
+
+    let mut status = EXIT_OK;
+    for path in markdown_files(&paths) {
+        let Some(text) = read(&path) else {
+            status = EXIT_ERROR;
+            continue;
+        };
+        for diagnostic in lint::lint(&text, &config) {
+            println!("{}:{}", path.display(), diagnostic);
+            status = status.max(EXIT_PROBLEMS);
+        }
+    }
+    status
+}
+
+/// `markzap links [--external] PATH...`: print the broken local links and
+/// images (and, with `--external`, `http(s)` links) as `path:line: problem`.
+fn check_links(args: &[String]) -> i32 {
+    let mut external = Settings::load().check_external_links;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--external" => external = true,
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("Usage: markzap links [--external] PATH...");
+        return EXIT_ERROR;
+    }
+
+    let client: Option<Arc<dyn HttpClient>> = if external {
+        Some(SimpleHttpClient::new())
+    } else {
+        None
+    };
+    let mut status = EXIT_OK;
+    for path in markdown_files(&paths) {
+        let Some(text) = read(&path) else {
+            status = EXIT_ERROR;
+            continue;
+        };
+        let broken =
+            futures::executor::block_on(links::check(&text, path.parent(), client.clone()));
+        for broken in broken {
+            println!("{}:{}", path.display(), broken);
+            status = status.max(EXIT_PROBLEMS);
+        }
+    }
+    status
+}
+
+/// `markzap fmt [--check] PATH...`: reformat files in place, or with
+/// `--check` only list those that are not formatted.
+fn fmt(args: &[String]) -> i32 {
+    let config = Settings::load().format;
+    let mut check = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("Usage: markzap fmt [--check] PATH...");
+        return EXIT_ERROR;
+    }
+
+    let mut status = EXIT_OK;
+    for path in markdown_files(&paths) {
+        let Some(text) = read(&path) else {
+            status = EXIT_ERROR;
+            continue;
+        };
+        let formatted = format::format(&text, &config);
+        if formatted == text {
+            continue;
+        }
+        if check {
+            println!("{}", path.display());
+            status = status.max(EXIT_PROBLEMS);
+        } else if let Err(e) = fs::write(&path, formatted) {
+            eprintln!("Error saving file {:?}: {}", path, e);
+            status = EXIT_ERROR;
+        }
+    }
+    status
+}
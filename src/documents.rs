@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gpui::*;
+
+use crate::app::AppView;
+use crate::session::Session;
+
+/// Canonical form of a document path, used as the registry key so that
+/// `./notes.md`, `notes.md` and symlinks all resolve to the same entry.
+pub fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+struct OpenDocument {
+    window: AnyWindowHandle,
+    views: Vec<WeakEntity<AppView>>,
+}
+
+/// Registry of the documents currently open, keyed by canonical path.
+///
+/// Used to focus the existing window instead of opening a duplicate, and to
+/// keep every view of the same file on one shared buffer.
+#[derive(Default)]
+pub struct DocumentRegistry {
+    documents: HashMap<PathBuf, OpenDocument>,
+    /// Every window showing documents, including the welcome screen, as
+    /// opposed to settings, search and presentation windows.
+    windows: Vec<AnyWindowHandle>,
+}
+
+impl Global for DocumentRegistry {}
+
+impl DocumentRegistry {
+    /// Record that `view`, shown in `window`, displays the file at `path`.
+    pub fn register(path: &Path, window: AnyWindowHandle, view: &Entity<AppView>, cx: &mut App) {
+        let registry = cx.default_global::<Self>();
+        let entry = registry
+            .documents
+            .entry(path.to_path_buf())
+            .or_insert_with(|| OpenDocument {
+                window,
+                views: Vec::new(),
+            });
+        entry.views.push(view.downgrade());
+    }
+
+    /// Record that `window` shows documents.
+    pub fn register_window(window: AnyWindowHandle, cx: &mut App) {
+        cx.default_global::<Self>().windows.push(window);
+    }
+
+    /// Forget `window` and every document shown in it (called when it closes).
+    pub fn unregister_window(window: AnyWindowHandle, cx: &mut App) {
+        let registry = cx.default_global::<Self>();
+        registry.documents.retain(|_, doc| doc.window != window);
+        registry.windows.retain(|w| *w != window);
+    }
+
+    /// Whether `window` shows documents.
+    pub fn is_document_window(window: AnyWindowHandle, cx: &App) -> bool {
+        cx.try_global::<Self>()
+            .is_some_and(|registry| registry.windows.contains(&window))
+    }
+
+    /// Document windows other than `window` that are still open.
+    fn other_windows(window: AnyWindowHandle, cx: &App) -> usize {
+        let open = cx.windows();
+        cx.try_global::<Self>().map_or(0, |registry| {
+            registry
+                .windows
+                .iter()
+                .filter(|w| **w != window && open.contains(w))
+                .count()
+        })
+    }
+
+    /// Forget `view` (a closed tab), and documents left without views.
+    pub fn unregister_view(view: &Entity<AppView>, cx: &mut App) {
+        let view = view.downgrade();
+        let registry = cx.default_global::<Self>();
+        for doc in registry.documents.values_mut() {
+            doc.views.retain(|v| *v != view);
+        }
+        registry.documents.retain(|_, doc| !doc.views.is_empty());
+    }
+
+    /// Bring the window showing `path` to the front. Returns that window and
+    /// the first live view of the document, or `None` if it is not open.
+    pub fn focus(path: &Path, cx: &mut App) -> Option<(AnyWindowHandle, Entity<AppView>)> {
+        let (window, view) = {
+            let doc = cx.try_global::<Self>()?.documents.get(path)?;
+            (doc.window, doc.views.iter().find_map(|v| v.upgrade()))
+        };
+        // Fails if the window has been closed behind our back.
+        let activated = window
+            .update(cx, |_, window, _| window.activate_window())
+            .is_ok();
+        match view {
+            Some(view) if activated => Some((window, view)),
+            _ => {
+                cx.default_global::<Self>().documents.remove(path);
+                None
+            }
+        }
+    }
+
+    /// All live views of the document at `path`.
+    pub fn views(path: &Path, cx: &App) -> Vec<Entity<AppView>> {
+        cx.try_global::<Self>()
+            .and_then(|registry| registry.documents.get(path))
+            .map(|doc| doc.views.iter().filter_map(|v| v.upgrade()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Live document views shown in `window`.
+    pub fn window_views(window: AnyWindowHandle, cx: &App) -> Vec<Entity<AppView>> {
+        cx.try_global::<Self>()
+            .map(|registry| {
+                registry
+                    .documents
+                    .values()
+                    .filter(|doc| doc.window == window)
+                    .flat_map(|doc| doc.views.iter().filter_map(|v| v.upgrade()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every live document view, in no particular order.
+    pub fn all_views(cx: &App) -> Vec<Entity<AppView>> {
+        cx.try_global::<Self>()
+            .map(|registry| {
+                registry
+                    .documents
+                    .values()
+                    .flat_map(|doc| doc.views.iter().filter_map(|v| v.upgrade()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Flush the documents of a closing document window. Saves the session and
+/// quits when it is the last window.
+pub fn close_window(window: AnyWindowHandle, cx: &mut App) {
+    for view in DocumentRegistry::window_views(window, cx) {
+        view.read(cx).save_to_file();
+    }
+    // Settings, search and presentation windows do not keep the app open.
+    let is_last = DocumentRegistry::other_windows(window, cx) == 0;
+    if is_last {
+        Session::save(cx);
+    }
+    DocumentRegistry::unregister_window(window, cx);
+    if is_last {
+        cx.quit();
+    }
+}
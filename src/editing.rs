@@ -0,0 +1,239 @@
+use std::ops::Range;
+
+/// A cursor position in the editor: zero-based line and character column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The result of a Markdown-aware editing command: the bytes `range` of the
+/// document are replaced with `text`, then the cursor goes to `cursor` in
+/// the new document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+    pub cursor: Cursor,
+}
+
+impl Edit {
+    /// The edit turning `old` into `new`, replacing only what changed so
+    /// the editor can undo it and keep its scroll position.
+    pub fn diff(old: &str, new: &str, cursor: Cursor) -> Self {
+        let (range, text) = changed_range(old, new);
+        Self {
+            range,
+            text: text.to_string(),
+            cursor,
+        }
+    }
+
+    /// The document after this edit of `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        text.replace_range(self.range.clone(), &self.text);
+        text
+    }
+}
+
+/// The bytes of `old` that differ from `new`, between their common prefix
+/// and suffix, and what replaces them in `new`.
+pub fn changed_range<'a>(old: &str, new: &'a str) -> (Range<usize>, &'a str) {
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(old.len().min(new.len()), |((ix, _), _)| ix);
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (prefix..old.len() - suffix, &new[prefix..new.len() - suffix])
+}
+
+/// The lines of `text`, keeping a final empty line after a trailing newline
+/// so that joining them with `\n` gives `text` back.
+pub fn lines(text: &str) -> Vec<String> {
+    text.split('\n').map(str::to_string).collect()
+}
+
+/// Join `lines` back into a document.
+pub fn join(lines: &[String]) -> String {
+    lines.join("\n")
+}
+
+/// Byte offset of character `column` in `line`, clamped to its end.
+pub fn byte_index(line: &str, column: usize) -> usize {
+    line.char_indices()
+        .nth(column)
+        .map_or(line.len(), |(index, _)| index)
+}
+
+/// Byte offset in `text` of UTF-16 offset `offset`, as the platform text
+/// input counts, clamped to its end.
+pub fn byte_index_utf16(text: &str, offset: usize) -> usize {
+    let mut utf16 = 0;
+    for (index, c) in text.char_indices() {
+        if utf16 >= offset {
+            return index;
+        }
+        utf16 += c.len_utf16();
+    }
+    text.len()
+}
+
+/// UTF-16 offset, as the platform text input counts, of byte `index` of
+/// `text`.
+pub fn utf16_index(text: &str, index: usize) -> usize {
+    text[..index.min(text.len())].encode_utf16().count()
+}
+
+/// Whether zero-based `line` is inside (or is a fence of) a fenced code
+/// block, where Markdown editing commands must leave the text alone.
+pub fn in_code_block(lines: &[String], line: usize) -> bool {
+    let mut open = None;
+    for (ix, text) in lines.iter().enumerate().take(line + 1) {
+        open = match (open, fence(text)) {
+            (Some(open), Some(marker)) if marker == open => {
+                if ix == line {
+                    return true;
+                }
+                None
+            }
+            (None, Some(marker)) => Some(marker),
+            (open, _) => open,
+        };
+    }
+    open.is_some()
+}
+
+/// The fence, `` ``` `` or `~~~`, that `line` opens or closes a code block
+/// with, if any. A block is closed by the same fence it was opened with.
+pub fn fence(line: &str) -> Option<&'static str> {
+    let trimmed = line.trim_start();
+    ["```", "~~~"]
+        .into_iter()
+        .find(|fence| trimmed.starts_with(fence))
+}
+
+/// Insert `block` at the cursor on lines of its own, separated by blank
+/// lines from text before or after the cursor, and put the cursor after it.
+pub fn insert_block(text: &str, cursor: Cursor, block: &str) -> Edit {
+    let mut lines = lines(text);
+    let line = cursor.line.min(lines.len() - 1);
+    let current = lines[line].clone();
+    let (before, after) = current.split_at(byte_index(&current, cursor.column));
+    let mut inserted: Vec<String> = Vec::new();
+    if !before.trim().is_empty() {
+        inserted.extend([before.to_string(), String::new()]);
+    }
+    inserted.extend(block.lines().map(str::to_string));
+    let cursor = Cursor {
+        line: line + inserted.len() - 1,
+        column: inserted.last().map_or(0, |last| last.chars().count()),
+    };
+    if !after.trim().is_empty() {
+        inserted.extend([String::new(), after.to_string()]);
+    }
+    lines.splice(line..=line, inserted);
+    Edit::diff(text, &join(&lines), cursor)
+}
+
+/// Insert `inserted` at the cursor and put the cursor after it.
+pub fn insert(text: &str, cursor: Cursor, inserted: &str) -> Edit {
+    let mut lines = lines(text);
+    let line = cursor.line.min(lines.len() - 1);
+    let at = byte_index(&lines[line], cursor.column);
+    let after = lines[line].split_off(at);
+    let before = std::mem::take(&mut lines[line]);
+    let mut new_lines: Vec<String> = format!("{}{}", before, inserted)
+        .split('\n')
+        .map(str::to_string)
+        .collect();
+    let cursor = Cursor {
+        line: line + new_lines.len() - 1,
+        column: new_lines.last().map_or(0, |last| last.chars().count()),
+    };
+    if let Some(last) = new_lines.last_mut() {
+        last.push_str(&after);
+    }
+    lines.splice(line..=line, new_lines);
+    Edit::diff(text, &join(&lines), cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: usize, column: usize) -> Cursor {
+        Cursor { line, column }
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        for text in ["", "a", "a\n", "a\n\nb"] {
+            assert_eq!(join(&lines(text)), text);
+        }
+    }
+
+    #[test]
+    fn diff_replaces_only_the_change() {
+        let edit = Edit::diff("one two three", "one 2 three", at(0, 5));
+        assert_eq!((edit.range, edit.text.as_str()), (4..7, "2"));
+        assert_eq!(Edit::diff("abc", "abc", at(0, 0)).range, 3..3);
+        assert_eq!(Edit::diff("ab", "abab", at(0, 0)).range, 2..2);
+        assert_eq!(Edit::diff("abab", "ab", at(0, 0)).range, 2..4);
+    }
+
+    #[test]
+    fn diff_keeps_characters_whole() {
+        // é and è share their first UTF-8 byte.
+        let edit = Edit::diff("café", "cafè", at(0, 4));
+        assert_eq!((edit.range.clone(), edit.text.as_str()), (3..5, "è"));
+        assert_eq!(edit.apply("café"), "cafè");
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        let text = "aé😀b";
+        assert_eq!(utf16_index(text, 3), 2);
+        assert_eq!(utf16_index(text, 7), 4);
+        assert_eq!(utf16_index(text, 100), 5);
+        assert_eq!(byte_index_utf16(text, 4), 7);
+        assert_eq!(byte_index_utf16(text, 100), text.len());
+    }
+
+    #[test]
+    fn code_blocks() {
+        let lines = lines("a\n```\nb\n~~~\n```\nc\n~~~\nd");
+        let inside: Vec<bool> = (0..lines.len())
+            .map(|ix| in_code_block(&lines, ix))
+            .collect();
+        assert_eq!(
+            inside,
+            vec![false, true, true, true, true, false, true, true]
+        );
+    }
+
+    #[test]
+    fn insert_at_cursor() {
+        let edit = insert("ab\ncd", at(1, 1), "X\nY");
+        assert_eq!(edit.apply("ab\ncd"), "ab\ncX\nYd");
+        assert_eq!(edit.cursor, at(2, 1));
+    }
+
+    #[test]
+    fn insert_block_on_its_own_lines() {
+        let text = "before after";
+        let edit = insert_block(text, at(0, 7), "| a |\n|---|");
+        assert_eq!(edit.apply(text), "before \n\n| a |\n|---|\n\nafter");
+        assert_eq!(edit.cursor, at(3, 5));
+
+        let edit = insert_block("", at(0, 0), "| a |\n|---|");
+        assert_eq!(edit.apply(""), "| a |\n|---|");
+    }
+}
@@ -0,0 +1,192 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+/// Search options shared by the editor find panel and the preview find bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Treat the query as a regular expression instead of literal text.
+    pub regex: bool,
+}
+
+/// Compile `query` into a regex honoring `options`. Returns `Ok(None)` for an
+/// empty query and an error for an invalid regular expression.
+fn build_regex(query: &str, options: FindOptions) -> Result<Option<Regex>, regex::Error> {
+    if query.is_empty() {
+        return Ok(None);
+    }
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let pattern = if options.whole_word {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map(Some)
+}
+
+/// Byte ranges of every non-empty match of `query` in `text`.
+pub fn find_matches(
+    text: &str,
+    query: &str,
+    options: FindOptions,
+) -> Result<Vec<Range<usize>>, regex::Error> {
+    let Some(regex) = build_regex(query, options)? else {
+        return Ok(Vec::new());
+    };
+    Ok(regex
+        .find_iter(text)
+        .map(|m| m.range())
+        .filter(|range| !range.is_empty())
+        .collect())
+}
+
+/// Replace the match at `range` (as returned by `find_matches`). In regex
+/// mode, `$1`-style group references in `replacement` are expanded.
+pub fn replace_one(
+    text: &str,
+    range: Range<usize>,
+    query: &str,
+    replacement: &str,
+    options: FindOptions,
+) -> Result<String, regex::Error> {
+    let Some(regex) = build_regex(query, options)? else {
+        return Ok(text.to_string());
+    };
+    let mut replaced = String::with_capacity(text.len());
+    replaced.push_str(&text[..range.start]);
+    let captures = regex
+        .captures_at(text, range.start)
+        .filter(|captures| captures.get(0).map(|m| m.range()) == Some(range.clone()));
+    match captures {
+        Some(captures) if options.regex => captures.expand(replacement, &mut replaced),
+        _ => replaced.push_str(replacement),
+    }
+    replaced.push_str(&text[range.end..]);
+    Ok(replaced)
+}
+
+/// Replace every non-empty match (those `find_matches` reports), returning
+/// the new text and the number of replacements.
+pub fn replace_all(
+    text: &str,
+    query: &str,
+    replacement: &str,
+    options: FindOptions,
+) -> Result<(String, usize), regex::Error> {
+    let Some(regex) = build_regex(query, options)? else {
+        return Ok((text.to_string(), 0));
+    };
+    let mut replaced = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut count = 0;
+    for captures in regex.captures_iter(text) {
+        let Some(m) = captures.get(0).filter(|m| !m.is_empty()) else {
+            continue;
+        };
+        replaced.push_str(&text[last..m.start()]);
+        if options.regex {
+            captures.expand(replacement, &mut replaced);
+        } else {
+            replaced.push_str(replacement);
+        }
+        last = m.end();
+        count += 1;
+    }
+    replaced.push_str(&text[last..]);
+    Ok((replaced, count))
+}
+
+/// Zero-based line and column (in characters) of byte `offset` in `text`.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LITERAL: FindOptions = FindOptions {
+        case_sensitive: false,
+        whole_word: false,
+        regex: false,
+    };
+    const REGEX: FindOptions = FindOptions {
+        regex: true,
+        ..LITERAL
+    };
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        assert!(build_regex("", LITERAL).unwrap().is_none());
+        assert!(find_matches("abc", "", LITERAL).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        assert!(build_regex("(", REGEX).is_err());
+        // The same text is fine as a literal.
+        assert_eq!(find_matches("a(b", "(", LITERAL).unwrap(), vec![1..2]);
+    }
+
+    #[test]
+    fn case_sensitivity() {
+        let text = "Été, été";
+        assert_eq!(find_matches(text, "été", LITERAL).unwrap().len(), 2);
+        let sensitive = FindOptions {
+            case_sensitive: true,
+            ..LITERAL
+        };
+        assert_eq!(find_matches(text, "été", sensitive).unwrap(), vec![7..12]);
+    }
+
+    #[test]
+    fn whole_word() {
+        let options = FindOptions {
+            whole_word: true,
+            ..LITERAL
+        };
+        assert_eq!(
+            find_matches("cat concat cat.", "cat", options).unwrap(),
+            vec![0..3, 11..14]
+        );
+        // Word boundaries are Unicode-aware.
+        assert!(find_matches("évité", "vit", options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn replace_one_literal_and_groups() {
+        let replaced = replace_one("a $1 b", 2..4, "$1", "x", LITERAL).unwrap();
+        assert_eq!(replaced, "a x b");
+        let replaced = replace_one("key=value", 0..9, r"(\w+)=(\w+)", "$2=$1", REGEX).unwrap();
+        assert_eq!(replaced, "value=key");
+    }
+
+    #[test]
+    fn replace_all_counts_replacements() {
+        let (replaced, count) = replace_all("a.b.c", ".", "$0", LITERAL).unwrap();
+        assert_eq!((replaced.as_str(), count), ("a$0b$0c", 2));
+        let (replaced, count) = replace_all("one two", r"(\w+)", "<$1>", REGEX).unwrap();
+        assert_eq!((replaced.as_str(), count), ("<one> <two>", 2));
+    }
+
+    #[test]
+    fn replace_all_skips_empty_matches() {
+        let (replaced, count) = replace_all("axxbx", "x*", "-", REGEX).unwrap();
+        assert_eq!((replaced.as_str(), count), ("a-b-", 2));
+        let (replaced, count) = replace_all("ab\ncd", "^", "> ", REGEX).unwrap();
+        assert_eq!((replaced.as_str(), count), ("ab\ncd", 0));
+    }
+}
@@ -0,0 +1,257 @@
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{Arena, ListStyleType, Options, format_commonmark, parse_document};
+use serde::{Deserialize, Serialize};
+
+use crate::render;
+use crate::slidev;
+use crate::table;
+
+/// The `[format]` table of `settings.toml`, used by Format Document and
+/// `markzap fmt`.
+///
+/// ```toml
+/// [format]
+/// list_marker = "*"
+/// emphasis = "_"
+/// wrap_width = 80
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    /// Bullet list marker: `-`, `*` or `+`.
+    pub list_marker: char,
+    /// Emphasis delimiter: `*` or `_`. Strong emphasis doubles it.
+    pub emphasis: char,
+    /// Wrap paragraphs at this many columns; 0 keeps lines unwrapped.
+    pub wrap_width: usize,
+    /// Pad table columns so the pipes line up.
+    pub align_tables: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            list_marker: '-',
+            emphasis: '*',
+            wrap_width: 0,
+            align_tables: true,
+        }
+    }
+}
+
+fn options(config: &FormatConfig) -> Options<'static> {
+    let mut options = render::markdown_options();
+    // Keep Slidev and Jekyll front matter as is.
+    options.extension.front_matter_delimiter = Some("---".to_string());
+    options.render.width = config.wrap_width;
+    options.render.list_style = match config.list_marker {
+        '*' => ListStyleType::Star,
+        '+' => ListStyleType::Plus,
+        _ => ListStyleType::Dash,
+    };
+    options.render.prefer_fenced = true;
+    options
+}
+
+/// Reformat `markdown` as CommonMark in the configured style.
+///
+/// Presentations are left unchanged: the formatter would read the front
+/// matter of each slide, after its `---` separator, as a setext heading.
+pub fn format(markdown: &str, config: &FormatConfig) -> String {
+    if slidev::detect_presentation(markdown) {
+        return markdown.to_string();
+    }
+    let options = options(config);
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &options);
+    let mut output = Vec::new();
+    if let Err(e) = format_commonmark(root, &options, &mut output) {
+        eprintln!("Error formatting document: {}", e);
+        return markdown.to_string();
+    }
+    let formatted = String::from_utf8_lossy(&output).into_owned();
+    restyle(&formatted, config, &options)
+}
+
+/// Apply what comrak's formatter does not offer to its output: `---`
+/// thematic breaks, the `_` emphasis style and aligned tables. Works from the sourcepos of the
+/// reparsed output, so positions are exact.
+fn restyle(formatted: &str, config: &FormatConfig, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, formatted, options);
+    let mut lines: Vec<String> = formatted.lines().map(str::to_string).collect();
+
+    // comrak writes `-----`; `---` is what people type.
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        if !matches!(data.value, NodeValue::ThematicBreak) {
+            continue;
+        }
+        let Some(line) = lines.get_mut(data.sourcepos.start.line.saturating_sub(1)) else {
+            continue;
+        };
+        if let Some(prefix) = line.strip_suffix("-----") {
+            *line = format!("{}---", prefix);
+        }
+    }
+
+    if config.emphasis == '_' {
+        for node in root.descendants() {
+            let delimiter_len = match node.data.borrow().value {
+                NodeValue::Emph => 1,
+                NodeValue::Strong => 2,
+                _ => continue,
+            };
+            restyle_emphasis(node, delimiter_len, &mut lines);
+        }
+    }
+
+    if config.align_tables {
+        // Bottom-up, so earlier line numbers stay valid.
+        let tables: Vec<_> = root
+            .children()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Table(_)))
+            .map(|node| {
+                let pos = node.data.borrow().sourcepos;
+                (pos.start.line.saturating_sub(1), pos.end.line)
+            })
+            .collect();
+        for (start, end) in tables.into_iter().rev() {
+            let rows: Vec<&str> = lines[start..end.min(lines.len())]
+                .iter()
+                .map(String::as_str)
+                .collect();
+            if let Some(aligned) = table::align(&rows) {
+                lines.splice(start..start + rows.len(), aligned);
+            }
+        }
+    }
+
+    let mut result = lines.join("\n");
+    if formatted.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Swap the `*` delimiters of an emphasis node for `_`, unless it is inside
+/// a word, where `_` would not be emphasis.
+fn restyle_emphasis<'a>(node: &'a AstNode<'a>, len: usize, lines: &mut [String]) {
+    let pos = node.data.borrow().sourcepos;
+    let (open_line, open) = (
+        pos.start.line.saturating_sub(1),
+        pos.start.column.saturating_sub(1),
+    );
+    let (close_line, close_end) = (pos.end.line.saturating_sub(1), pos.end.column);
+    let (Some(open_text), Some(close_text)) = (lines.get(open_line), lines.get(close_line)) else {
+        return;
+    };
+    let Some(close) = close_end.checked_sub(len) else {
+        return;
+    };
+    let stars = "*".repeat(len);
+    let in_word = open_text
+        .get(..open)
+        .and_then(|before| before.chars().next_back())
+        .is_some_and(char::is_alphanumeric)
+        || close_text
+            .get(close_end..)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(char::is_alphanumeric);
+    if in_word
+        || open_text.get(open..open + len) != Some(stars.as_str())
+        || close_text.get(close..close_end) != Some(stars.as_str())
+    {
+        return;
+    }
+    let underscores = "_".repeat(len);
+    lines[open_line].replace_range(open..open + len, &underscores);
+    lines[close_line].replace_range(close..close_end, &underscores);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn underscores() -> FormatConfig {
+        FormatConfig {
+            emphasis: '_',
+            ..FormatConfig::default()
+        }
+    }
+
+    #[test]
+    fn thematic_breaks_stay_short() {
+        let formatted = format("One\n\n***\n\nTwo\n\n- - -\n", &FormatConfig::default());
+        assert_eq!(formatted, "One\n\n---\n\nTwo\n\n---\n");
+        let quoted = format("> One\n>\n> ***\n", &FormatConfig::default());
+        assert!(quoted.ends_with("> ---\n"), "{}", quoted);
+    }
+
+    #[test]
+    fn presentations_are_unchanged() {
+        let slides = "---\ntheme: default\n---\n\n# One\n\n---\nlayout: center\n---\n\n\
+            # Two\n\n* a\n";
+        assert_eq!(format(slides, &FormatConfig::default()), slides);
+        let separators = "# One\n\n---\n\n# Two\n\n---\n\n# Three\n\n---\n\n*Four*\n";
+        assert_eq!(format(separators, &underscores()), separators);
+    }
+
+    #[test]
+    fn front_matter_is_kept() {
+        let markdown = "---\ntitle: Notes\n---\n\n# Notes\n";
+        assert_eq!(format(markdown, &FormatConfig::default()), markdown);
+    }
+
+    #[test]
+    fn list_marker() {
+        let config = FormatConfig {
+            list_marker: '*',
+            ..FormatConfig::default()
+        };
+        assert_eq!(format("- a\n- b\n", &config), "* a\n* b\n");
+    }
+
+    #[test]
+    fn underscore_emphasis() {
+        assert_eq!(
+            format("Some *em*, **strong** and ***both***.\n", &underscores()),
+            "Some _em_, __strong__ and ___both___.\n"
+        );
+    }
+
+    #[test]
+    fn emphasis_inside_words_keeps_stars() {
+        assert_eq!(
+            format("un*frigging*believable and **a**b\n", &underscores()),
+            "un*frigging*believable and **a**b\n"
+        );
+    }
+
+    #[test]
+    fn restyle_emphasis_across_lines() {
+        let options = options(&underscores());
+        assert_eq!(
+            restyle("*one\ntwo*\n", &underscores(), &options),
+            "_one\ntwo_\n"
+        );
+    }
+
+    #[test]
+    fn tables_are_aligned() {
+        let formatted = format(
+            "| a | bbb |\n|---|---|\n| cc | d |\n",
+            &FormatConfig::default(),
+        );
+        let widths: Vec<usize> = formatted.lines().map(|line| line.chars().count()).collect();
+        assert!(widths.windows(2).all(|w| w[0] == w[1]), "{}", formatted);
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let markdown =
+            "# Title\n\nSome *text*\n\n***\n\n* a\n* b\n\n| x | y |\n|---|---|\n| 1 | 2 |\n";
+        let once = format(markdown, &underscores());
+        assert_eq!(format(&once, &underscores()), once);
+    }
+}
@@ -0,0 +1,106 @@
+/// A candidate matched by `fuzzy_match`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better.
+    pub score: i64,
+    /// Byte offsets of the matched characters in the candidate.
+    pub positions: Vec<usize>,
+}
+
+/// Match `query` as a case-insensitive subsequence of `candidate`.
+///
+/// Consecutive characters and characters starting a word (after a path
+/// separator, punctuation or a lowercase-to-uppercase change) score higher;
+/// gaps and long candidates score lower. Returns `None` if some query
+/// character is missing. Spaces in the query are ignored.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let mut query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut score = 0;
+    let mut positions = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+
+    for (index, (offset, c)) in candidate.char_indices().enumerate() {
+        let Some(&wanted) = query.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1;
+            let word_start = match previous {
+                None => true,
+                Some(p) => {
+                    matches!(p, '/' | '\\' | '_' | '-' | '.' | ' ')
+                        || (p.is_lowercase() && c.is_uppercase())
+                }
+            };
+            if word_start {
+                score += 8;
+            }
+            match last_match {
+                Some(last) if last + 1 == index => score += 5,
+                Some(last) => score -= (index - last - 1).min(5) as i64,
+                None => {}
+            }
+            last_match = Some(index);
+            positions.push(offset);
+            query.next();
+        }
+        previous = Some(c);
+    }
+
+    if query.peek().is_some() {
+        return None;
+    }
+    // Prefer shorter candidates among otherwise equal matches.
+    score -= (candidate.chars().count() / 16) as i64;
+    Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> i64 {
+        fuzzy_match(query, candidate).unwrap().score
+    }
+
+    #[test]
+    fn subsequence_in_any_case() {
+        let found = fuzzy_match("RdMe", "docs/readme.md").unwrap();
+        assert_eq!(found.positions, vec![5, 8, 9, 10]);
+        assert!(fuzzy_match("xyz", "readme.md").is_none());
+        assert!(fuzzy_match("mdr", "readme.md").is_none());
+    }
+
+    #[test]
+    fn spaces_in_query_are_ignored() {
+        assert_eq!(
+            fuzzy_match("read me", "readme.md").unwrap().positions.len(),
+            6
+        );
+    }
+
+    #[test]
+    fn positions_are_byte_offsets() {
+        let found = fuzzy_match("nt", "été/notes.md").unwrap();
+        assert_eq!(found.positions, vec![6, 8]);
+    }
+
+    #[test]
+    fn word_starts_and_runs_score_higher() {
+        // `n` starts a word after the separator.
+        assert!(score("n", "docs/notes.md") > score("n", "docs/inotes.md"));
+        assert!(score("nb", "NoteBook.md") > score("nb", "Noteabook.md"));
+        // Consecutive characters beat a gap.
+        assert!(score("not", "notes.md") > score("not", "nxoxtes.md"));
+    }
+
+    #[test]
+    fn shorter_candidates_win_ties() {
+        assert!(score("a", "a.md") > score("a", &format!("a{}.md", "x".repeat(40))));
+    }
+}
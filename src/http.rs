@@ -1,8 +1,13 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::FutureExt as _;
 use gpui::http_client::{AsyncBody, HttpClient, Response, Url};
 
+/// How long a request may take before it fails, so that a host that never
+/// answers cannot stall its caller.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
 pub struct SimpleHttpClient {
     client: reqwest::Client,
     runtime: tokio::runtime::Runtime,
@@ -16,7 +21,12 @@ impl SimpleHttpClient {
             .expect("Failed to create Tokio runtime");
 
         // Build the reqwest client inside the Tokio runtime context
-        let client = runtime.block_on(async { reqwest::Client::new() });
+        let client = runtime.block_on(async {
+            reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to create HTTP client")
+        });
 
         Arc::new(Self { client, runtime })
     }
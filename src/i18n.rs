@@ -0,0 +1,391 @@
+use gpui::App;
+
+use crate::settings::Settings;
+
+/// Message catalog: every user-facing string of the UI, menus and preview page.
+pub struct Strings {
+    // Menus
+    pub menu_file: &'static str,
+    pub menu_open: &'static str,
+    pub menu_open_recent: &'static str,
+    pub menu_clear_recent: &'static str,
+    pub menu_reopen_session: &'static str,
+    pub menu_export_html: &'static str,
+    pub menu_settings: &'static str,
+    pub menu_quit: &'static str,
+    pub menu_edit: &'static str,
+    pub menu_find: &'static str,
+    pub menu_search_in_folder: &'static str,
+    pub menu_format_document: &'static str,
+    pub menu_toggle_task: &'static str,
+    pub menu_format: &'static str,
+    pub menu_bold: &'static str,
+    pub menu_italic: &'static str,
+    pub menu_code: &'static str,
+    pub menu_link: &'static str,
+    pub menu_heading: &'static str,
+    pub menu_quote: &'static str,
+    pub menu_code_block: &'static str,
+    pub menu_table: &'static str,
+    pub menu_add_row: &'static str,
+    pub menu_remove_row: &'static str,
+    pub menu_add_column: &'static str,
+    pub menu_remove_column: &'static str,
+    pub menu_sort_table: &'static str,
+    pub menu_quick_open: &'static str,
+    pub menu_view: &'static str,
+    pub menu_command_palette: &'static str,
+    pub menu_toggle_mode: &'static str,
+    pub menu_present: &'static str,
+    pub menu_go_to_heading: &'static str,
+    pub menu_zoom_in: &'static str,
+    pub menu_zoom_out: &'static str,
+    pub menu_reset_zoom: &'static str,
+    pub menu_problems: &'static str,
+    pub menu_check_links: &'static str,
+    pub menu_close_tab: &'static str,
+    pub menu_close_window: &'static str,
+    pub menu_next_tab: &'static str,
+    pub menu_previous_tab: &'static str,
+    pub menu_appearance: &'static str,
+    pub menu_preview_theme: &'static str,
+    pub menu_language: &'static str,
+    pub appearance_auto: &'static str,
+    pub appearance_light: &'static str,
+    pub appearance_dark: &'static str,
+    pub language_auto: &'static str,
+
+    // Welcome screen and errors shown as documents
+    pub welcome: &'static str,
+    pub read_error_title: &'static str,
+    pub read_error_body: &'static str,
+
+    // Editor find panel and preview find bar
+    pub find_placeholder: &'static str,
+    pub find_previous: &'static str,
+    pub find_next: &'static str,
+    pub find_close: &'static str,
+    pub find_case_sensitive: &'static str,
+    pub find_whole_word: &'static str,
+    pub find_regex: &'static str,
+    /// Joins the current match and the total, as in "3 of 17".
+    pub find_of: &'static str,
+    pub find_no_results: &'static str,
+
+    // Find and replace
+    pub find_invalid_regex: &'static str,
+    pub replace_placeholder: &'static str,
+    pub replace: &'static str,
+    pub replace_all: &'static str,
+
+    // Lint problems panel
+    pub problems_none: &'static str,
+    pub links_checking: &'static str,
+    pub links_none: &'static str,
+
+    // Folder search window
+    pub search_title: &'static str,
+    pub search_placeholder: &'static str,
+    pub search_choose_folder: &'static str,
+    pub search_no_folder: &'static str,
+    pub search_indexing: &'static str,
+    /// Follows the number of indexed files, as in "42 files indexed".
+    pub search_files_indexed: &'static str,
+
+    // Pickers
+    pub quick_open_placeholder: &'static str,
+    pub command_palette_placeholder: &'static str,
+    pub go_to_heading_placeholder: &'static str,
+
+    // Windows and dialogs
+    pub presentation_title: &'static str,
+    pub settings_title: &'static str,
+    pub settings_autosave_delay: &'static str,
+    pub settings_window_width: &'static str,
+    pub settings_editor_font: &'static str,
+    pub settings_content_width: &'static str,
+    pub settings_restore_session: &'static str,
+    pub settings_open_in_tabs: &'static str,
+    pub settings_format_toolbar: &'static str,
+    pub settings_save: &'static str,
+    pub settings_invalid_autosave_delay: &'static str,
+    pub settings_invalid_window_width: &'static str,
+    pub settings_invalid_content_width: &'static str,
+    pub settings_empty_editor_font: &'static str,
+    pub settings_save_failed: &'static str,
+}
+
+pub const EN: Strings = Strings {
+    menu_file: "File",
+    menu_open: "Open\u{2026}",
+    menu_open_recent: "Open Recent",
+    menu_clear_recent: "Clear Recently Opened",
+    menu_reopen_session: "Reopen Last Session",
+    menu_export_html: "Export HTML\u{2026}",
+    menu_settings: "Settings\u{2026}",
+    menu_quit: "Quit MarkZap",
+    menu_edit: "Edit",
+    menu_find: "Find\u{2026}",
+    menu_search_in_folder: "Search in Folder\u{2026}",
+    menu_format_document: "Format Document",
+    menu_toggle_task: "Toggle Task",
+    menu_format: "Format",
+    menu_bold: "Bold",
+    menu_italic: "Italic",
+    menu_code: "Code",
+    menu_link: "Link",
+    menu_heading: "Heading Level",
+    menu_quote: "Quote",
+    menu_code_block: "Code Block",
+    menu_table: "Table",
+    menu_add_row: "Add Row",
+    menu_remove_row: "Remove Row",
+    menu_add_column: "Add Column",
+    menu_remove_column: "Remove Column",
+    menu_sort_table: "Sort by Column",
+    menu_quick_open: "Quick Open\u{2026}",
+    menu_view: "View",
+    menu_command_palette: "Command Palette\u{2026}",
+    menu_toggle_mode: "Toggle Editor",
+    menu_present: "Start Presentation",
+    menu_go_to_heading: "Go to Heading\u{2026}",
+    menu_zoom_in: "Zoom In",
+    menu_zoom_out: "Zoom Out",
+    menu_reset_zoom: "Actual Size",
+    menu_problems: "Problems",
+    menu_check_links: "Check Links",
+    menu_close_tab: "Close Tab",
+    menu_close_window: "Close Window",
+    menu_next_tab: "Next Tab",
+    menu_previous_tab: "Previous Tab",
+    menu_appearance: "Appearance",
+    menu_preview_theme: "Preview Theme",
+    menu_language: "Language",
+    appearance_auto: "Match System",
+    appearance_light: "Light",
+    appearance_dark: "Dark",
+    language_auto: "Match System",
+
+    welcome: "# Welcome to MarkZap\n\n\
+              Open a `.md` file by passing it as a command-line argument:\n\n\
+              ```\n\
+              markzap path/to/file.md\n\
+              ```\n\n\
+              Or double-click a `.md` file to open it with MarkZap.",
+    read_error_title: "Error",
+    read_error_body: "Could not read",
+
+    find_placeholder: "Find\u{2026}",
+    find_previous: "Previous",
+    find_next: "Next",
+    find_close: "Close",
+    find_case_sensitive: "Match Case",
+    find_whole_word: "Whole Word",
+    find_regex: "Regular Expression",
+    find_of: "of",
+    find_no_results: "No results",
+
+    find_invalid_regex: "Invalid regex",
+    replace_placeholder: "Replace\u{2026}",
+    replace: "Replace",
+    replace_all: "Replace All",
+
+    problems_none: "No problems",
+    links_checking: "Checking links\u{2026}",
+    links_none: "No broken links",
+
+    search_title: "Search in Folder",
+    search_placeholder: "Search Markdown files\u{2026}",
+    search_choose_folder: "Choose Folder\u{2026}",
+    search_no_folder: "No folder selected",
+    search_indexing: "Indexing\u{2026}",
+    search_files_indexed: "files indexed",
+
+    quick_open_placeholder: "Go to file\u{2026}",
+    command_palette_placeholder: "Type a command\u{2026}",
+    go_to_heading_placeholder: "Go to heading\u{2026}",
+
+    presentation_title: "MarkZap Presentation",
+    settings_title: "MarkZap Settings",
+    settings_autosave_delay: "Autosave delay (ms)",
+    settings_window_width: "New window width (px)",
+    settings_editor_font: "Editor font",
+    settings_content_width: "Preview content width (px)",
+    settings_restore_session: "Restore previous session",
+    settings_open_in_tabs: "Open documents in tabs",
+    settings_format_toolbar: "Show formatting toolbar",
+    settings_save: "Save",
+    settings_invalid_autosave_delay: "Autosave delay must be a whole number of milliseconds",
+    settings_invalid_window_width: "Window width must be a number",
+    settings_invalid_content_width: "Content width must be a whole number of pixels",
+    settings_empty_editor_font: "Editor font cannot be empty",
+    settings_save_failed: "Could not save settings",
+};
+
+pub const FR: Strings = Strings {
+    menu_file: "Fichier",
+    menu_open: "Ouvrir\u{2026}",
+    menu_open_recent: "Ouvrir un fichier récent",
+    menu_clear_recent: "Effacer les fichiers récents",
+    menu_reopen_session: "Rouvrir la dernière session",
+    menu_export_html: "Exporter en HTML\u{2026}",
+    menu_settings: "Réglages\u{2026}",
+    menu_quit: "Quitter MarkZap",
+    menu_edit: "Édition",
+    menu_find: "Rechercher\u{2026}",
+    menu_search_in_folder: "Rechercher dans le dossier\u{2026}",
+    menu_format_document: "Mettre en forme le document",
+    menu_toggle_task: "Cocher/décocher la tâche",
+    menu_format: "Format",
+    menu_bold: "Gras",
+    menu_italic: "Italique",
+    menu_code: "Code",
+    menu_link: "Lien",
+    menu_heading: "Niveau de titre",
+    menu_quote: "Citation",
+    menu_code_block: "Bloc de code",
+    menu_table: "Tableau",
+    menu_add_row: "Ajouter une ligne",
+    menu_remove_row: "Supprimer la ligne",
+    menu_add_column: "Ajouter une colonne",
+    menu_remove_column: "Supprimer la colonne",
+    menu_sort_table: "Trier par colonne",
+    menu_quick_open: "Ouverture rapide\u{2026}",
+    menu_view: "Présentation",
+    menu_command_palette: "Palette de commandes\u{2026}",
+    menu_toggle_mode: "Basculer l’éditeur",
+    menu_present: "Lancer le diaporama",
+    menu_go_to_heading: "Aller au titre\u{2026}",
+    menu_zoom_in: "Zoom avant",
+    menu_zoom_out: "Zoom arrière",
+    menu_reset_zoom: "Taille réelle",
+    menu_problems: "Problèmes",
+    menu_check_links: "Vérifier les liens",
+    menu_close_tab: "Fermer l’onglet",
+    menu_close_window: "Fermer la fenêtre",
+    menu_next_tab: "Onglet suivant",
+    menu_previous_tab: "Onglet précédent",
+    menu_appearance: "Apparence",
+    menu_preview_theme: "Thème de l’aperçu",
+    menu_language: "Langue",
+    appearance_auto: "Selon le système",
+    appearance_light: "Clair",
+    appearance_dark: "Sombre",
+    language_auto: "Selon le système",
+
+    welcome: "# Bienvenue dans MarkZap\n\n\
+              Ouvrez un fichier `.md` en le passant en argument de la ligne de commande :\n\n\
+              ```\n\
+              markzap chemin/vers/fichier.md\n\
+              ```\n\n\
+              Ou double-cliquez sur un fichier `.md` pour l’ouvrir avec MarkZap.",
+    read_error_title: "Erreur",
+    read_error_body: "Impossible de lire",
+
+    find_placeholder: "Rechercher\u{2026}",
+    find_previous: "Précédent",
+    find_next: "Suivant",
+    find_close: "Fermer",
+    find_case_sensitive: "Respecter la casse",
+    find_whole_word: "Mot entier",
+    find_regex: "Expression régulière",
+    find_of: "sur",
+    find_no_results: "Aucun résultat",
+
+    find_invalid_regex: "Expression invalide",
+    replace_placeholder: "Remplacer par\u{2026}",
+    replace: "Remplacer",
+    replace_all: "Tout remplacer",
+
+    problems_none: "Aucun problème",
+    links_checking: "Vérification des liens\u{2026}",
+    links_none: "Aucun lien cassé",
+
+    search_title: "Rechercher dans un dossier",
+    search_placeholder: "Rechercher dans les fichiers Markdown\u{2026}",
+    search_choose_folder: "Choisir un dossier\u{2026}",
+    search_no_folder: "Aucun dossier sélectionné",
+    search_indexing: "Indexation\u{2026}",
+    search_files_indexed: "fichiers indexés",
+
+    quick_open_placeholder: "Aller au fichier\u{2026}",
+    command_palette_placeholder: "Tapez une commande\u{2026}",
+    go_to_heading_placeholder: "Aller au titre\u{2026}",
+
+    presentation_title: "MarkZap — Présentation",
+    settings_title: "Réglages de MarkZap",
+    settings_autosave_delay: "Délai d’enregistrement (ms)",
+    settings_window_width: "Largeur des nouvelles fenêtres (px)",
+    settings_editor_font: "Police de l’éditeur",
+    settings_content_width: "Largeur du contenu de l’aperçu (px)",
+    settings_restore_session: "Restaurer la session précédente",
+    settings_open_in_tabs: "Ouvrir les documents dans des onglets",
+    settings_format_toolbar: "Afficher la barre de mise en forme",
+    settings_save: "Enregistrer",
+    settings_invalid_autosave_delay: "Le délai d’enregistrement doit être un nombre entier de millisecondes",
+    settings_invalid_window_width: "La largeur de fenêtre doit être un nombre",
+    settings_invalid_content_width: "La largeur du contenu doit être un nombre entier de pixels",
+    settings_empty_editor_font: "La police de l’éditeur ne peut pas être vide",
+    settings_save_failed: "Impossible d’enregistrer les réglages",
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    French,
+}
+
+impl Language {
+    pub const ALL: [Self; 2] = [Self::English, Self::French];
+
+    /// Match a BCP 47 / POSIX locale tag (`fr-FR`, `fr_CA.UTF-8`, `en`) on its
+    /// primary language.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Self::English),
+            "fr" => Some(Self::French),
+            _ => None,
+        }
+    }
+
+    /// Tag stored in the settings file.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::French => "fr",
+        }
+    }
+
+    /// Name of the language in itself, as shown in the Language menu.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::French => "Français",
+        }
+    }
+
+    /// The OS language, or English if it has no catalog.
+    pub fn system() -> Self {
+        sys_locale::get_locale()
+            .and_then(|tag| Self::from_tag(&tag))
+            .unwrap_or(Self::English)
+    }
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Self::English => &EN,
+            Self::French => &FR,
+        }
+    }
+}
+
+/// The language chosen in the settings (`"auto"` follows the OS).
+pub fn language(cx: &App) -> Language {
+    Language::from_tag(&Settings::get(cx).language).unwrap_or_else(Language::system)
+}
+
+/// Message catalog of the current language.
+pub fn tr(cx: &App) -> &'static Strings {
+    language(cx).strings()
+}
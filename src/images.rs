@@ -0,0 +1,142 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use gpui::ImageFormat;
+
+/// Folder, next to the document, that pasted and dropped images go to.
+const ASSETS_DIR: &str = "assets";
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "tiff"];
+
+/// Whether `path` names an image file, judging by its extension.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// File extension for an image of `format`.
+pub fn extension(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Webp => "webp",
+        ImageFormat::Gif => "gif",
+        ImageFormat::Svg => "svg",
+        ImageFormat::Bmp => "bmp",
+        ImageFormat::Tiff => "tiff",
+    }
+}
+
+/// A free path in the assets folder of `document`, named after it:
+/// `assets/notes-1.png`, `assets/notes-2.png`, ...
+fn generated_path(document: &Path, extension: &str) -> PathBuf {
+    let dir = document.parent().unwrap_or(Path::new(".")).join(ASSETS_DIR);
+    let stem = document
+        .file_stem()
+        .map_or("image".into(), |stem| stem.to_string_lossy());
+    (1..)
+        .map(|n| dir.join(format!("{}-{}.{}", stem, n, extension)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+/// Save image `bytes` to the assets folder of `document` and return the
+/// Markdown reference to it.
+pub fn save(document: &Path, bytes: &[u8], extension: &str) -> io::Result<String> {
+    let path = generated_path(document, extension);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, bytes)?;
+    Ok(reference(&path))
+}
+
+/// Copy the image file at `source` to the assets folder of `document` and
+/// return the Markdown reference to the copy.
+pub fn copy(document: &Path, source: &Path) -> io::Result<String> {
+    let extension = source.extension().map_or("png".to_string(), |ext| {
+        ext.to_string_lossy().to_lowercase()
+    });
+    save(document, &fs::read(source)?, &extension)
+}
+
+/// `![name](assets/name.png)` for an image saved in the assets folder, with
+/// the file stem as alt text.
+fn reference(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let alt = path.file_stem().unwrap_or_default().to_string_lossy();
+    let alt = alt.replace('[', "\\[").replace(']', "\\]");
+    let target = format!("{}/{}", ASSETS_DIR, name);
+    // Destinations with spaces must be written in angle brackets.
+    if target.contains(' ') {
+        format!("![{}](<{}>)", alt, target)
+    } else {
+        format!("![{}]({})", alt, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty folder holding the document `notes.md`.
+    fn document(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("markzap-images-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("notes.md")
+    }
+
+    #[test]
+    fn numbering_skips_existing_files() {
+        let document = document("numbering");
+        let assets = document.parent().unwrap().join(ASSETS_DIR);
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("notes-1.png"), b"").unwrap();
+        fs::write(assets.join("notes-2.png"), b"").unwrap();
+        assert_eq!(generated_path(&document, "png"), assets.join("notes-3.png"));
+        assert_eq!(generated_path(&document, "jpg"), assets.join("notes-1.jpg"));
+    }
+
+    #[test]
+    fn save_creates_assets_folder() {
+        let document = document("save");
+        let reference = save(&document, b"image", "png").unwrap();
+        assert_eq!(reference, "![notes-1](assets/notes-1.png)");
+        let path = document.parent().unwrap().join("assets/notes-1.png");
+        assert_eq!(fs::read(path).unwrap(), b"image");
+    }
+
+    #[test]
+    fn copy_lowercases_extension() {
+        let document = document("copy");
+        let source = document.parent().unwrap().join("Photo.JPG");
+        fs::write(&source, b"photo").unwrap();
+        assert_eq!(
+            copy(&document, &source).unwrap(),
+            "![notes-1](assets/notes-1.jpg)"
+        );
+        assert!(
+            document
+                .parent()
+                .unwrap()
+                .join("assets/notes-1.jpg")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn reference_with_spaces() {
+        assert_eq!(
+            reference(Path::new("/docs/assets/my notes-1.png")),
+            "![my notes-1](<assets/my notes-1.png>)"
+        );
+        assert_eq!(
+            reference(Path::new("/docs/assets/notes-2.gif")),
+            "![notes-2](assets/notes-2.gif)"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use gpui::*;
+
+use crate::paths;
+use crate::{
+    AddTableColumn, AddTableRow, CheckLinks, CloseTab, CommandPalette, CycleHeading, ExportHtml,
+    Find, FormatDocument, GoToHeading, Indent, InsertLink, Newline, NextTab, OpenFile,
+    OpenSettings, Outdent, PasteMarkdown, PreviousTab, QuickOpen, Quit, RemoveTableColumn,
+    RemoveTableRow, ResetZoom, RestoreSession, SearchInFolder, SortTable, StartPresentation,
+    ToggleBold, ToggleCode, ToggleCodeBlock, ToggleItalic, ToggleMode, ToggleProblems, ToggleQuote,
+    ToggleTask, ZoomIn, ZoomOut,
+};
+
+const KEYMAP_FILE: &str = "keymap.toml";
+
+/// Context of the bindings that act on a document window.
+const DOCUMENT: Option<&str> = Some("AppView");
+
+/// Context of the Markdown editor, where these bindings take precedence over
+/// the editor's own (their actions fall back to it when they do not apply).
+const EDITOR: Option<&str> = Some("MarkdownEditor > Input");
+
+/// Default bindings. `secondary` is Cmd on macOS and Ctrl elsewhere.
+const DEFAULT_KEYMAP: &[(&str, &str)] = &[
+    ("secondary-o", "OpenFile"),
+    ("secondary-p", "QuickOpen"),
+    ("secondary-shift-p", "CommandPalette"),
+    ("secondary-f", "Find"),
+    ("secondary-shift-f", "SearchInFolder"),
+    ("alt-shift-f", "FormatDocument"),
+    ("secondary-shift-o", "GoToHeading"),
+    ("secondary-e", "ToggleMode"),
+    ("secondary-shift-m", "ToggleProblems"),
+    ("secondary-=", "ZoomIn"),
+    ("secondary--", "ZoomOut"),
+    ("secondary-0", "ResetZoom"),
+    ("secondary-w", "CloseTab"),
+    ("ctrl-tab", "NextTab"),
+    ("ctrl-shift-tab", "PreviousTab"),
+    ("secondary-,", "OpenSettings"),
+    ("secondary-q", "Quit"),
+    ("enter", "Newline"),
+    ("tab", "Indent"),
+    ("shift-tab", "Outdent"),
+    ("secondary-v", "PasteMarkdown"),
+    ("secondary-alt-x", "ToggleTask"),
+    ("secondary-b", "ToggleBold"),
+    ("secondary-i", "ToggleItalic"),
+    ("secondary-`", "ToggleCode"),
+    ("secondary-k", "InsertLink"),
+    ("secondary-alt-h", "CycleHeading"),
+    ("secondary-alt-q", "ToggleQuote"),
+    ("secondary-alt-c", "ToggleCodeBlock"),
+];
+
+/// Build the binding of `keystrokes` to the action called `name` (with or
+/// without its `markzap::` namespace), or `None` for an unknown action.
+fn binding(keystrokes: &str, name: &str) -> Option<KeyBinding> {
+    let name = name.strip_prefix("markzap::").unwrap_or(name);
+    Some(match name {
+        "OpenFile" => KeyBinding::new(keystrokes, OpenFile, None),
+        "RestoreSession" => KeyBinding::new(keystrokes, RestoreSession, None),
+        "OpenSettings" => KeyBinding::new(keystrokes, OpenSettings, None),
+        "SearchInFolder" => KeyBinding::new(keystrokes, SearchInFolder, None),
+        "Quit" => KeyBinding::new(keystrokes, Quit, None),
+        "QuickOpen" => KeyBinding::new(keystrokes, QuickOpen, DOCUMENT),
+        "CommandPalette" => KeyBinding::new(keystrokes, CommandPalette, DOCUMENT),
+        "Find" => KeyBinding::new(keystrokes, Find, DOCUMENT),
+        "GoToHeading" => KeyBinding::new(keystrokes, GoToHeading, DOCUMENT),
+        "ToggleMode" => KeyBinding::new(keystrokes, ToggleMode, DOCUMENT),
+        "ToggleProblems" => KeyBinding::new(keystrokes, ToggleProblems, DOCUMENT),
+        "CheckLinks" => KeyBinding::new(keystrokes, CheckLinks, DOCUMENT),
+        "FormatDocument" => KeyBinding::new(keystrokes, FormatDocument, DOCUMENT),
+        "StartPresentation" => KeyBinding::new(keystrokes, StartPresentation, DOCUMENT),
+        "ExportHtml" => KeyBinding::new(keystrokes, ExportHtml, DOCUMENT),
+        "ZoomIn" => KeyBinding::new(keystrokes, ZoomIn, DOCUMENT),
+        "ZoomOut" => KeyBinding::new(keystrokes, ZoomOut, DOCUMENT),
+        "ResetZoom" => KeyBinding::new(keystrokes, ResetZoom, DOCUMENT),
+        "CloseTab" => KeyBinding::new(keystrokes, CloseTab, DOCUMENT),
+        "NextTab" => KeyBinding::new(keystrokes, NextTab, DOCUMENT),
+        "PreviousTab" => KeyBinding::new(keystrokes, PreviousTab, DOCUMENT),
+        "Newline" => KeyBinding::new(keystrokes, Newline, EDITOR),
+        "Indent" => KeyBinding::new(keystrokes, Indent, EDITOR),
+        "Outdent" => KeyBinding::new(keystrokes, Outdent, EDITOR),
+        "PasteMarkdown" => KeyBinding::new(keystrokes, PasteMarkdown, EDITOR),
+        "ToggleTask" => KeyBinding::new(keystrokes, ToggleTask, DOCUMENT),
+        "ToggleBold" => KeyBinding::new(keystrokes, ToggleBold, EDITOR),
+        "ToggleItalic" => KeyBinding::new(keystrokes, ToggleItalic, EDITOR),
+        "ToggleCode" => KeyBinding::new(keystrokes, ToggleCode, EDITOR),
+        "InsertLink" => KeyBinding::new(keystrokes, InsertLink, EDITOR),
+        "CycleHeading" => KeyBinding::new(keystrokes, CycleHeading, EDITOR),
+        "ToggleQuote" => KeyBinding::new(keystrokes, ToggleQuote, EDITOR),
+        "ToggleCodeBlock" => KeyBinding::new(keystrokes, ToggleCodeBlock, EDITOR),
+        "AddTableRow" => KeyBinding::new(keystrokes, AddTableRow, DOCUMENT),
+        "RemoveTableRow" => KeyBinding::new(keystrokes, RemoveTableRow, DOCUMENT),
+        "AddTableColumn" => KeyBinding::new(keystrokes, AddTableColumn, DOCUMENT),
+        "RemoveTableColumn" => KeyBinding::new(keystrokes, RemoveTableColumn, DOCUMENT),
+        "SortTable" => KeyBinding::new(keystrokes, SortTable, DOCUMENT),
+        _ => return None,
+    })
+}
+
+/// Replace the `secondary` modifier with the platform's command modifier.
+fn for_platform(keystrokes: &str) -> String {
+    let modifier = if cfg!(target_os = "macos") {
+        "cmd-"
+    } else {
+        "ctrl-"
+    };
+    keystrokes
+        .split(' ')
+        .map(|keystroke| match keystroke.strip_prefix("secondary-") {
+            Some(key) => format!("{}{}", modifier, key),
+            None => keystroke.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn path() -> Option<PathBuf> {
+    paths::config_dir().map(|dir| dir.join(KEYMAP_FILE))
+}
+
+/// User overrides from `keymap.toml`: keystrokes mapped to an action name,
+/// or to `""` to remove a default binding.
+///
+/// ```toml
+/// "ctrl-k ctrl-p" = "CommandPalette"
+/// "secondary-e" = ""
+/// ```
+fn load_user_keymap() -> BTreeMap<String, String> {
+    let Some(path) = path() else {
+        return BTreeMap::new();
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("Ignoring invalid keymap {:?}: {}", path, e);
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// The bindings for this platform, keystrokes to action name: the defaults
+/// overridden by `user`, without those the user removed.
+fn keymap(user: BTreeMap<String, String>) -> BTreeMap<String, String> {
+    let mut keymap: BTreeMap<String, String> = DEFAULT_KEYMAP
+        .iter()
+        .map(|&(keys, action)| (for_platform(keys), action.to_string()))
+        .collect();
+    for (keys, action) in user {
+        keymap.insert(for_platform(&keys), action);
+    }
+    keymap.retain(|_, action| !action.is_empty());
+    keymap
+}
+
+/// Install the default bindings for this platform, overridden by the user
+/// keymap. Read once at launch: gpui cannot remove bindings selectively, and
+/// clearing them all would drop those of the gpui-component inputs.
+pub fn load(cx: &mut App) {
+    let bindings: Vec<KeyBinding> = keymap(load_user_keymap())
+        .iter()
+        .filter_map(|(keys, action)| {
+            let binding = binding(keys, action);
+            if binding.is_none() {
+                eprintln!(
+                    "Ignoring key binding {:?}: unknown action {:?}",
+                    keys, action
+                );
+            }
+            binding
+        })
+        .collect();
+    cx.bind_keys(bindings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The platform's command modifier, as `for_platform` writes it.
+    fn secondary(key: &str) -> String {
+        let modifier = if cfg!(target_os = "macos") {
+            "cmd"
+        } else {
+            "ctrl"
+        };
+        format!("{}-{}", modifier, key)
+    }
+
+    fn user(toml: &str) -> BTreeMap<String, String> {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn secondary_is_the_platform_modifier() {
+        assert_eq!(for_platform("secondary-e"), secondary("e"));
+        assert_eq!(for_platform("secondary-shift-p"), secondary("shift-p"));
+        assert_eq!(
+            for_platform("secondary-k secondary-p"),
+            format!("{} {}", secondary("k"), secondary("p"))
+        );
+        assert_eq!(for_platform("ctrl-tab"), "ctrl-tab");
+        assert_eq!(for_platform("alt-shift-f"), "alt-shift-f");
+    }
+
+    #[test]
+    fn defaults() {
+        let keymap = keymap(BTreeMap::new());
+        assert_eq!(keymap.len(), DEFAULT_KEYMAP.len());
+        assert_eq!(keymap[&secondary("e")], "ToggleMode");
+        assert!(!keymap.contains_key("secondary-e"));
+    }
+
+    #[test]
+    fn user_binding_overrides_default() {
+        let keymap = keymap(user(
+            r#"
+            "secondary-e" = "CommandPalette"
+            "ctrl-k ctrl-p" = "QuickOpen"
+            "#,
+        ));
+        assert_eq!(keymap[&secondary("e")], "CommandPalette");
+        assert_eq!(keymap["ctrl-k ctrl-p"], "QuickOpen");
+        assert_eq!(keymap.len(), DEFAULT_KEYMAP.len() + 1);
+    }
+
+    #[test]
+    fn empty_action_removes_binding() {
+        let keymap = keymap(user(r#""secondary-e" = """#));
+        assert!(!keymap.contains_key(&secondary("e")));
+        assert_eq!(keymap.len(), DEFAULT_KEYMAP.len() - 1);
+    }
+
+    #[test]
+    fn default_actions_exist() {
+        for (keys, action) in keymap(BTreeMap::new()) {
+            assert!(binding(&keys, &action).is_some(), "{}", action);
+        }
+        assert!(binding("ctrl-e", "markzap::ToggleMode").is_some());
+        assert!(binding("ctrl-e", "Unknown").is_none());
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use comrak::nodes::NodeValue;
+use comrak::{Anchorizer, Arena, parse_document};
+use futures::StreamExt as _;
+use gpui::http_client::{AsyncBody, HttpClient, Request};
+use regex::Regex;
+
+use crate::outline;
+use crate::render;
+use crate::search;
+use crate::url;
+
+/// A link or image of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub url: String,
+    /// Zero-based line where the link starts.
+    pub line: usize,
+    pub is_image: bool,
+}
+
+/// Why a link is broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The local file or folder does not exist.
+    MissingTarget(PathBuf),
+    /// No heading (or HTML element) of the target has this id.
+    MissingAnchor(String),
+    /// The server answered with an error status.
+    HttpStatus(u16),
+    /// The request failed (DNS, TLS, timeout...).
+    Unreachable(String),
+}
+
+/// A broken link and where it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    pub link: Link,
+    pub problem: Problem,
+}
+
+impl BrokenLink {
+    /// What is broken, without the line number.
+    pub fn description(&self) -> String {
+        let what = if self.link.is_image { "Image" } else { "Link" };
+        let problem = match self.problem {
+            Problem::MissingTarget(ref path) => format!("{} not found", path.display()),
+            Problem::MissingAnchor(ref anchor) => format!("no heading #{}", anchor),
+            Problem::HttpStatus(status) => format!("HTTP {}", status),
+            Problem::Unreachable(ref error) => format!("unreachable ({})", error),
+        };
+        format!("{} {}: {}", what, self.link.url, problem)
+    }
+}
+
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.link.line + 1, self.description())
+    }
+}
+
+/// Every link and image of `markdown`, in document order.
+pub fn links(markdown: &str) -> Vec<Link> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &render::markdown_options());
+    root.descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            let (url, is_image) = match data.value {
+                NodeValue::Link(ref link) => (link.url.clone(), false),
+                NodeValue::Image(ref link) => (link.url.clone(), true),
+                _ => return None,
+            };
+            Some(Link {
+                url,
+                line: data.sourcepos.start.line.saturating_sub(1),
+                is_image,
+            })
+        })
+        .collect()
+}
+
+/// The ids `#fragment` links can target in `markdown`: the heading ids the
+/// preview generates, and `id` attributes of raw HTML.
+pub fn anchors(markdown: &str) -> HashSet<String> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &render::markdown_options());
+    let html_id = Regex::new(r#"\bid\s*=\s*["']([^"']+)["']"#).expect("valid id pattern");
+    let mut anchorizer = Anchorizer::new();
+    let mut anchors = HashSet::new();
+    for node in root.descendants() {
+        match node.data.borrow().value {
+            NodeValue::Heading(_) => {
+                anchors.insert(anchorizer.anchorize(outline::plain_text(node)));
+            }
+            NodeValue::HtmlBlock(ref html) => {
+                anchors.extend(
+                    html_id
+                        .captures_iter(&html.literal)
+                        .map(|c| c[1].to_string()),
+                );
+            }
+            NodeValue::HtmlInline(ref html) => {
+                anchors.extend(html_id.captures_iter(html).map(|c| c[1].to_string()));
+            }
+            _ => {}
+        }
+    }
+    anchors
+}
+
+/// Whether `url` is checked over HTTP rather than on disk.
+pub fn is_external(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Check the local links of `markdown`: files relative to `base_dir` (the
+/// document's folder) and `#anchors`, including those into other Markdown
+/// files. Without `base_dir` only same-document anchors are checked.
+pub fn check_local(markdown: &str, base_dir: Option<&Path>) -> Vec<BrokenLink> {
+    let own_anchors = anchors(markdown);
+    let mut broken = Vec::new();
+    for link in links(markdown) {
+        // Other schemes (mailto:, tel:, ...) and protocol-relative URLs.
+        if url::is_url(&link.url) || link.url.starts_with("//") {
+            continue;
+        }
+        let (target, fragment) = match link.url.split_once('#') {
+            Some((target, fragment)) => (target, Some(fragment)),
+            None => (link.url.as_str(), None),
+        };
+        let target = target.split('?').next().unwrap_or_default();
+
+        let problem = if target.is_empty() {
+            fragment
+                .filter(|fragment| !fragment.is_empty() && !own_anchors.contains(*fragment))
+                .map(|fragment| Problem::MissingAnchor(fragment.to_string()))
+        } else if let Some(base_dir) = base_dir {
+            let path = base_dir.join(url::decode_path(target));
+            if !path.exists() {
+                Some(Problem::MissingTarget(path))
+            } else {
+                match fragment {
+                    Some(fragment) if !fragment.is_empty() && search::is_markdown(&path) => {
+                        let found = fs::read_to_string(&path)
+                            .map(|text| anchors(&text).contains(fragment))
+                            .unwrap_or(false);
+                        (!found).then(|| Problem::MissingAnchor(fragment.to_string()))
+                    }
+                    _ => None,
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            broken.push(BrokenLink { link, problem });
+        }
+    }
+    broken
+}
+
+/// Check one external URL: a HEAD request, retried as GET for servers that
+/// do not support HEAD.
+pub async fn check_external(client: Arc<dyn HttpClient>, url: &str) -> Option<Problem> {
+    let mut status = None;
+    for method in ["HEAD", "GET"] {
+        let request = match Request::builder()
+            .method(method)
+            .uri(url)
+            .body(AsyncBody::empty())
+        {
+            Ok(request) => request,
+            Err(e) => return Some(Problem::Unreachable(e.to_string())),
+        };
+        match client.send(request).await {
+            Ok(response) if response.status().is_success() => return None,
+            Ok(response) => status = Some(response.status().as_u16()),
+            Err(e) => return Some(Problem::Unreachable(e.to_string())),
+        }
+        // Only "method not allowed" style answers are worth a GET.
+        if !matches!(status, Some(403 | 405 | 501)) {
+            break;
+        }
+    }
+    status.map(Problem::HttpStatus)
+}
+
+/// How many external links are checked at the same time.
+const MAX_CONCURRENT_CHECKS: usize = 8;
+
+/// Check the local links of `markdown` and, with `client`, its external
+/// ones, in document order. External links are checked a few at a time.
+pub async fn check(
+    markdown: &str,
+    base_dir: Option<&Path>,
+    client: Option<Arc<dyn HttpClient>>,
+) -> Vec<BrokenLink> {
+    let mut broken = check_local(markdown, base_dir);
+    if let Some(client) = client {
+        let external = links(markdown)
+            .into_iter()
+            .filter(|link| is_external(&link.url))
+            .map(|link| {
+                let client = client.clone();
+                async move {
+                    let problem = check_external(client, &link.url).await?;
+                    Some(BrokenLink { link, problem })
+                }
+            });
+        let checked: Vec<_> = futures::stream::iter(external)
+            .buffered(MAX_CONCURRENT_CHECKS)
+            .collect()
+            .await;
+        broken.extend(checked.into_iter().flatten());
+        broken.sort_by_key(|broken| broken.link.line);
+    }
+    broken
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead as _, BufReader, Write as _};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::http::SimpleHttpClient;
+
+    /// Answer every request on a local port with `status(method, path)`, from
+    /// a background thread. Returns the server's base URL.
+    fn serve(status: fn(&str, &str) -> u16) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                reader.read_line(&mut request).ok();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                    header.clear();
+                }
+                let mut parts = request.split_whitespace();
+                let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status(method, path)
+                );
+                (&stream).write_all(response.as_bytes()).ok();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    fn check_url(url: &str) -> Option<Problem> {
+        futures::executor::block_on(check_external(SimpleHttpClient::new(), url))
+    }
+
+    #[test]
+    fn external_links() {
+        let base = serve(|method, path| match (method, path) {
+            (_, "/ok") => 200,
+            ("HEAD", "/get-only") => 405,
+            ("GET", "/get-only") => 200,
+            ("HEAD", "/forbidden") => 405,
+            _ => 404,
+        });
+        assert_eq!(check_url(&format!("{}/ok", base)), None);
+        assert_eq!(
+            check_url(&format!("{}/missing", base)),
+            Some(Problem::HttpStatus(404))
+        );
+        // HEAD is refused, GET works.
+        assert_eq!(check_url(&format!("{}/get-only", base)), None);
+        // The status of the GET is the one reported.
+        assert_eq!(
+            check_url(&format!("{}/forbidden", base)),
+            Some(Problem::HttpStatus(404))
+        );
+    }
+
+    #[test]
+    fn checks_in_document_order() {
+        let base = serve(|_, path| if path.ends_with("-ok") { 200 } else { 404 });
+        let markdown: String = (0..20)
+            .map(|n| {
+                let suffix = if n % 3 == 0 { "missing" } else { "ok" };
+                format!("[{}]({}/{}-{})\n\n", n, base, n, suffix)
+            })
+            .collect();
+        let client: Arc<dyn HttpClient> = SimpleHttpClient::new();
+        let broken = futures::executor::block_on(check(&markdown, None, Some(client)));
+        let lines: Vec<_> = broken.iter().map(|broken| broken.link.line).collect();
+        assert_eq!(lines, [0, 6, 12, 18, 24, 30, 36]);
+        assert!(
+            broken
+                .iter()
+                .all(|broken| broken.problem == Problem::HttpStatus(404))
+        );
+    }
+
+    #[test]
+    fn unreachable_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        assert!(matches!(
+            check_url(&format!("http://{}/", address)),
+            Some(Problem::Unreachable(_))
+        ));
+    }
+
+    /// A fresh folder holding `files`.
+    fn folder(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("markzap-links-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        for (file, text) in files {
+            fs::write(dir.join(file), text).unwrap();
+        }
+        dir
+    }
+
+    fn problems(markdown: &str, base_dir: Option<&Path>) -> Vec<(usize, Problem)> {
+        check_local(markdown, base_dir)
+            .into_iter()
+            .map(|broken| (broken.link.line, broken.problem))
+            .collect()
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = folder("missing", &[("here.md", "# Here\n")]);
+        let markdown = "[a](here.md)\n\n[b](gone.md)\n\n![c](my%20image.png)\n";
+        assert_eq!(
+            problems(markdown, Some(&dir)),
+            vec![
+                (2, Problem::MissingTarget(dir.join("gone.md"))),
+                (4, Problem::MissingTarget(dir.join("my image.png"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_anchor() {
+        let markdown = "# Getting Started\n\n[a](#getting-started)\n\n[b](#usage)\n";
+        assert_eq!(
+            problems(markdown, None),
+            vec![(4, Problem::MissingAnchor("usage".into()))]
+        );
+    }
+
+    #[test]
+    fn anchor_into_another_document() {
+        let dir = folder(
+            "anchors",
+            &[
+                ("guide.md", "# Install\n\n<a id=\"faq\"></a>\n"),
+                ("notes.txt", ""),
+            ],
+        );
+        let markdown = "[a](guide.md#install)\n\n[b](guide.md#faq)\n\n\
+            [c](guide.md#usage)\n\n[d](notes.txt#anything)\n";
+        assert_eq!(
+            problems(markdown, Some(&dir)),
+            vec![(4, Problem::MissingAnchor("usage".into()))]
+        );
+    }
+
+    #[test]
+    fn other_schemes_are_skipped() {
+        let markdown = "[a](mailto:me@example.com)\n\n[b](tel:123)\n\n\
+            [c](//example.com/x)\n\n[d](https://example.com/gone)\n";
+        assert!(problems(markdown, Some(Path::new("/nonexistent"))).is_empty());
+    }
+
+    #[test]
+    fn files_are_not_checked_without_a_folder() {
+        assert!(problems("[a](gone.md)\n", None).is_empty());
+    }
+}
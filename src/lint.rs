@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use comrak::nodes::{AstNode, ListType, NodeValue};
+use comrak::{Arena, parse_document};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::outline;
+use crate::render;
+
+/// A style rule checked by the linter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// Heading levels only go down one level at a time (`#` then `##`).
+    HeadingIncrement,
+    /// No two headings have the same text.
+    DuplicateHeading,
+    /// No whitespace at the end of lines, except a two-space hard break.
+    TrailingSpaces,
+    /// URLs are written as links or `<autolinks>`, not bare text.
+    BareUrl,
+    /// Images have alternative text.
+    ImageAltText,
+    /// Bullet lists all use the same marker (`-`, `*` or `+`).
+    ListMarkerStyle,
+}
+
+impl Rule {
+    pub const ALL: [Self; 6] = [
+        Self::HeadingIncrement,
+        Self::DuplicateHeading,
+        Self::TrailingSpaces,
+        Self::BareUrl,
+        Self::ImageAltText,
+        Self::ListMarkerStyle,
+    ];
+
+    /// Identifier used in settings and in reports.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::HeadingIncrement => "heading-increment",
+            Self::DuplicateHeading => "no-duplicate-headings",
+            Self::TrailingSpaces => "no-trailing-spaces",
+            Self::BareUrl => "no-bare-urls",
+            Self::ImageAltText => "image-alt-text",
+            Self::ListMarkerStyle => "list-marker-style",
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// The `[lint]` table of `settings.toml`.
+///
+/// ```toml
+/// [lint]
+/// disabled_rules = ["no-trailing-spaces"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// Lint documents while editing.
+    pub enabled: bool,
+    /// Ids of the rules to skip.
+    pub disabled_rules: Vec<String>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            disabled_rules: Vec::new(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        !self.disabled_rules.iter().any(|id| id == rule.id())
+    }
+}
+
+/// A problem found by the linter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub message: String,
+    /// Zero-based line.
+    pub line: usize,
+    /// Zero-based character columns of the offending text on `line`.
+    pub start_column: usize,
+    pub end_column: usize,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} [{}]",
+            self.line + 1,
+            self.start_column + 1,
+            self.message,
+            self.rule
+        )
+    }
+}
+
+/// Collects diagnostics, dropping those of disabled rules.
+struct Report<'a> {
+    config: &'a LintConfig,
+    lines: Vec<&'a str>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report<'_> {
+    /// Report `rule` over the bytes `start..end` of zero-based `line`;
+    /// `end` is clamped to the line.
+    fn push(&mut self, rule: Rule, line: usize, start: usize, end: usize, message: String) {
+        if !self.config.is_enabled(rule) {
+            return;
+        }
+        let text = self.lines.get(line).copied().unwrap_or_default();
+        let end = end.min(text.len()).max(start);
+        self.diagnostics.push(Diagnostic {
+            rule,
+            message,
+            line,
+            start_column: char_column(text, start),
+            end_column: char_column(text, end),
+        });
+    }
+}
+
+/// Character count of the first `byte` bytes of `line`, rounded down to a
+/// character boundary.
+fn char_column(line: &str, byte: usize) -> usize {
+    let mut byte = byte.min(line.len());
+    while !line.is_char_boundary(byte) {
+        byte -= 1;
+    }
+    line[..byte].chars().count()
+}
+
+/// Whether `node` is inside a link or an image.
+fn in_link<'a>(node: &'a AstNode<'a>) -> bool {
+    node.ancestors().skip(1).any(|ancestor| {
+        matches!(
+            ancestor.data.borrow().value,
+            NodeValue::Link(_) | NodeValue::Image(_)
+        )
+    })
+}
+
+/// Check `markdown` against the enabled rules, in document order.
+pub fn lint(markdown: &str, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut report = Report {
+        config,
+        lines: markdown.lines().collect(),
+        diagnostics: Vec::new(),
+    };
+
+    // Without the autolink extension, bare URLs stay plain text.
+    let mut options = render::markdown_options();
+    options.extension.autolink = false;
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &options);
+
+    let url = Regex::new(r"(?:https?|ftp)://[^\s<>]+").expect("valid URL pattern");
+    let mut previous_level = None;
+    let mut headings: HashMap<String, usize> = HashMap::new();
+    let mut bullet = None;
+
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        let line = data.sourcepos.start.line.saturating_sub(1);
+        let column = data.sourcepos.start.column.saturating_sub(1);
+        let line_end = report.lines.get(line).map_or(0, |l| l.len());
+        match data.value {
+            NodeValue::Heading(ref heading) => {
+                if let Some(previous) = previous_level {
+                    if heading.level > previous + 1 {
+                        report.push(
+                            Rule::HeadingIncrement,
+                            line,
+                            column,
+                            line_end,
+                            format!(
+                                "Heading level {} follows level {}; expected at most {}",
+                                heading.level,
+                                previous,
+                                previous + 1
+                            ),
+                        );
+                    }
+                }
+                previous_level = Some(heading.level);
+
+                let text = outline::plain_text(node).trim().to_string();
+                match headings.get(&text) {
+                    Some(&first) => report.push(
+                        Rule::DuplicateHeading,
+                        line,
+                        column,
+                        line_end,
+                        format!("Duplicate heading {:?} (first on line {})", text, first + 1),
+                    ),
+                    None => {
+                        headings.insert(text, line);
+                    }
+                }
+            }
+            // Link text such as `[https://x](https://x)` or `<https://x>`
+            // is already a link.
+            NodeValue::Text(ref text) if !in_link(node) => {
+                if let Some(found) = url.find(text) {
+                    // Text nodes may stop at punctuation inside the URL, so
+                    // take its full extent from the source line.
+                    let source = report.lines.get(line).copied().unwrap_or_default();
+                    let (start, end) = source
+                        .get(column..)
+                        .and_then(|rest| {
+                            let at = rest.find(found.as_str())?;
+                            let full = url.find_at(rest, at)?;
+                            Some((column + full.start(), column + full.end()))
+                        })
+                        .unwrap_or((column, column + found.len()));
+                    report.push(
+                        Rule::BareUrl,
+                        line,
+                        start,
+                        end,
+                        format!(
+                            "Bare URL {}; use <{}> or a link",
+                            found.as_str(),
+                            found.as_str()
+                        ),
+                    );
+                }
+            }
+            NodeValue::Image(ref link) => {
+                if outline::plain_text(node).trim().is_empty() {
+                    let end_line = data.sourcepos.end.line.saturating_sub(1);
+                    let end = if end_line == line {
+                        data.sourcepos.end.column
+                    } else {
+                        line_end
+                    };
+                    report.push(
+                        Rule::ImageAltText,
+                        line,
+                        column,
+                        end,
+                        format!("Image {} has no alternative text", link.url),
+                    );
+                }
+            }
+            NodeValue::List(ref list) if list.list_type == ListType::Bullet => {
+                let marker = list.bullet_char as char;
+                match bullet {
+                    None => bullet = Some(marker),
+                    Some(expected) if expected != marker => report.push(
+                        Rule::ListMarkerStyle,
+                        line,
+                        column,
+                        column + 1,
+                        format!(
+                            "List marker '{}' differs from '{}' used earlier",
+                            marker, expected
+                        ),
+                    ),
+                    Some(_) => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for line in 0..report.lines.len() {
+        let text = report.lines[line];
+        let trimmed = text.trim_end();
+        let trailing = &text[trimmed.len()..];
+        // Two spaces after text is a hard line break.
+        if trailing.is_empty() || (trailing == "  " && !trimmed.is_empty()) {
+            continue;
+        }
+        report.push(
+            Rule::TrailingSpaces,
+            line,
+            trimmed.len(),
+            text.len(),
+            "Trailing whitespace".to_string(),
+        );
+    }
+
+    let mut diagnostics = report.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.start_column));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(markdown: &str) -> Vec<(Rule, usize)> {
+        lint(markdown, &LintConfig::default())
+            .into_iter()
+            .map(|d| (d.rule, d.line))
+            .collect()
+    }
+
+    #[test]
+    fn clean_document() {
+        let markdown = "# Title\n\n## Section\n\n- a\n- b\n\n![Logo](logo.png)\n";
+        assert!(rules(markdown).is_empty());
+    }
+
+    #[test]
+    fn heading_increment() {
+        assert_eq!(
+            rules("# Title\n\n### Skipped\n\n## Back\n"),
+            vec![(Rule::HeadingIncrement, 2)]
+        );
+    }
+
+    #[test]
+    fn duplicate_heading() {
+        let diagnostics = lint("# A\n\n## B\n\n## *B*\n", &LintConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::DuplicateHeading);
+        assert_eq!(diagnostics[0].line, 4);
+        assert!(diagnostics[0].message.contains("line 3"));
+    }
+
+    #[test]
+    fn trailing_spaces() {
+        let diagnostics = lint("one \ntwo  \n  \nthree\t\n", &LintConfig::default());
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.rule, d.line, d.start_column, d.end_column))
+            .collect();
+        // A two-space hard break is allowed, but not on a blank line.
+        assert_eq!(
+            found,
+            vec![
+                (Rule::TrailingSpaces, 0, 3, 4),
+                (Rule::TrailingSpaces, 2, 0, 2),
+                (Rule::TrailingSpaces, 3, 5, 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_url() {
+        let diagnostics = lint(
+            "See https://example.com/a_(b) now.\n",
+            &LintConfig::default(),
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::BareUrl);
+        assert_eq!(
+            (diagnostics[0].start_column, diagnostics[0].end_column),
+            (4, 29)
+        );
+    }
+
+    #[test]
+    fn urls_in_links_are_not_bare() {
+        let markdown = "<https://example.com>\n\n\
+            [https://example.com](https://example.com)\n\n\
+            ![https://example.com/a.png](a.png)\n\n\
+            `https://example.com`\n";
+        assert!(rules(markdown).is_empty());
+    }
+
+    #[test]
+    fn image_alt_text() {
+        assert_eq!(
+            rules("![](a.png)\n\n![ ](b.png)\n\n![B](b.png)\n"),
+            vec![(Rule::ImageAltText, 0), (Rule::ImageAltText, 2)]
+        );
+    }
+
+    #[test]
+    fn list_marker_style() {
+        assert_eq!(
+            rules("- a\n- b\n\n* c\n\n1. d\n\n- e\n"),
+            vec![(Rule::ListMarkerStyle, 3)]
+        );
+    }
+
+    #[test]
+    fn disabled_rules_are_skipped() {
+        let config = LintConfig {
+            enabled: true,
+            disabled_rules: vec!["no-trailing-spaces".into(), "image-alt-text".into()],
+        };
+        let diagnostics = lint("# A \n\n![](a.png)\n\n### C\n", &config);
+        let rules: Vec<_> = diagnostics.iter().map(|d| d.rule).collect();
+        assert_eq!(rules, vec![Rule::HeadingIncrement]);
+        assert!(!config.is_enabled(Rule::TrailingSpaces));
+        assert!(config.is_enabled(Rule::BareUrl));
+    }
+}
@@ -0,0 +1,550 @@
+use crate::editing::{self, Cursor, Edit};
+
+/// The marker of a list item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Bullet(char),
+    /// `width` is the number of digits of zero-padded numbers such as
+    /// `01.`, and 1 otherwise.
+    Ordered {
+        number: u64,
+        delimiter: char,
+        width: usize,
+    },
+}
+
+impl Marker {
+    fn text(self) -> String {
+        match self {
+            Self::Bullet(c) => c.to_string(),
+            Self::Ordered {
+                number,
+                delimiter,
+                width,
+            } => format!("{:0width$}{}", number, delimiter, width = width),
+        }
+    }
+
+    /// The same marker with number `number`.
+    fn numbered(self, number: u64) -> Self {
+        match self {
+            Self::Bullet(_) => self,
+            Self::Ordered {
+                delimiter, width, ..
+            } => Self::Ordered {
+                number,
+                delimiter,
+                width,
+            },
+        }
+    }
+
+    /// The marker of the item that follows this one.
+    fn next(self) -> Self {
+        match self {
+            Self::Bullet(_) => self,
+            Self::Ordered { number, .. } => self.numbered(number + 1),
+        }
+    }
+}
+
+/// The Markdown prefix of a line: blockquote markers, indentation, list
+/// marker and task box. Offsets are in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Prefix {
+    /// End of the `>` markers of enclosing blockquotes.
+    quote_end: usize,
+    /// End of the indentation after them, where the list marker starts.
+    indent_end: usize,
+    marker: Option<Marker>,
+    /// End of the marker and the spaces after it.
+    marker_end: usize,
+    /// Whether the task box is checked, and where its `[` is.
+    task: Option<(bool, usize)>,
+    /// Where the text of the line starts.
+    content: usize,
+}
+
+impl Prefix {
+    fn parse(line: &str) -> Self {
+        let bytes = line.as_bytes();
+        let spaces = |from: usize| {
+            from + bytes[from..]
+                .iter()
+                .take_while(|&&b| b == b' ' || b == b'\t')
+                .count()
+        };
+
+        let mut quote_end = 0;
+        loop {
+            let at = spaces(quote_end);
+            if bytes.get(at) != Some(&b'>') {
+                break;
+            }
+            quote_end = at + 1;
+            if bytes.get(quote_end) == Some(&b' ') {
+                quote_end += 1;
+            }
+        }
+        let indent_end = spaces(quote_end);
+
+        let digits = bytes[indent_end..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let (marker, marker_len) = match bytes.get(indent_end + digits) {
+            Some(&c @ (b'-' | b'*' | b'+')) if digits == 0 => (Some(Marker::Bullet(c as char)), 1),
+            Some(&c @ (b'.' | b')')) if (1..=9).contains(&digits) => (
+                line[indent_end..indent_end + digits]
+                    .parse()
+                    .ok()
+                    .map(|number| Marker::Ordered {
+                        number,
+                        delimiter: c as char,
+                        width: if bytes[indent_end] == b'0' { digits } else { 1 },
+                    }),
+                digits + 1,
+            ),
+            _ => (None, 0),
+        };
+        // A marker must be followed by a space or the end of the line.
+        let after_marker = indent_end + marker_len;
+        let marker =
+            marker.filter(|_| matches!(bytes.get(after_marker), None | Some(b' ' | b'\t')));
+        let Some(marker) = marker else {
+            return Self {
+                quote_end,
+                indent_end,
+                marker: None,
+                marker_end: indent_end,
+                task: None,
+                content: indent_end,
+            };
+        };
+
+        let marker_end = spaces(after_marker);
+        let task = match bytes.get(marker_end..marker_end + 3) {
+            Some(b"[ ]") => Some(false),
+            Some(b"[x]" | b"[X]") => Some(true),
+            _ => None,
+        }
+        .filter(|_| matches!(bytes.get(marker_end + 3), None | Some(b' ' | b'\t')));
+        let content = match task {
+            Some(_) => spaces(marker_end + 3),
+            None => marker_end,
+        };
+        Self {
+            quote_end,
+            indent_end,
+            marker: Some(marker),
+            marker_end,
+            task: task.map(|checked| (checked, marker_end)),
+            content,
+        }
+    }
+
+    fn indent(&self) -> usize {
+        self.indent_end - self.quote_end
+    }
+}
+
+/// Enter: continue the list item or blockquote at the cursor on a new
+/// line, or end it if the item is empty.
+pub fn newline(text: &str, cursor: Cursor) -> Option<Edit> {
+    let mut lines = editing::lines(text);
+    if editing::in_code_block(&lines, cursor.line) {
+        return None;
+    }
+    let line = lines.get(cursor.line)?.clone();
+    let prefix = Prefix::parse(&line);
+    if prefix.marker.is_none() && prefix.quote_end == 0 {
+        return None;
+    }
+    let at = editing::byte_index(&line, cursor.column);
+    if at < prefix.content {
+        return None;
+    }
+
+    if line[prefix.content..].trim().is_empty() {
+        // Enter on an empty item ends the list (or the blockquote).
+        let kept = match prefix.marker {
+            Some(_) => line[..prefix.quote_end].trim_end(),
+            None => line[..prefix.quote_end]
+                .trim_end()
+                .trim_end_matches('>')
+                .trim_end(),
+        };
+        lines[cursor.line] = kept.to_string();
+        let cursor = Cursor {
+            line: cursor.line,
+            column: kept.chars().count(),
+        };
+        return Some(renumbered(text, lines, cursor));
+    }
+
+    let mut continuation = line[..prefix.indent_end].to_string();
+    if let Some(marker) = prefix.marker {
+        continuation.push_str(&marker.next().text());
+        continuation.push_str(&line[prefix.indent_end + marker.text().len()..prefix.marker_end]);
+        if prefix.marker_end == prefix.indent_end + marker.text().len() {
+            continuation.push(' ');
+        }
+        if prefix.task.is_some() {
+            continuation.push_str("[ ] ");
+        }
+    }
+    let rest = line[at..].trim_start().to_string();
+    lines[cursor.line] = line[..at].trim_end().to_string();
+    let cursor = Cursor {
+        line: cursor.line + 1,
+        column: continuation.chars().count(),
+    };
+    lines.insert(cursor.line, continuation + &rest);
+    Some(renumbered(text, lines, cursor))
+}
+
+/// Tab: nest the list item at the cursor under the previous item.
+pub fn indent(text: &str, cursor: Cursor) -> Option<Edit> {
+    let mut lines = editing::lines(text);
+    if editing::in_code_block(&lines, cursor.line) {
+        return None;
+    }
+    let mut prefix = Prefix::parse(lines.get(cursor.line)?);
+    prefix.marker?;
+    // Items nest under the text of the previous sibling; the first item of a
+    // list has nothing to nest under.
+    let (sibling_ix, sibling) = lines[..cursor.line]
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(ix, line)| (ix, Prefix::parse(line)))
+        .take_while(|(ix, p)| !lines[*ix].trim().is_empty() && p.indent() >= prefix.indent())
+        .find(|(_, p)| p.marker.is_some() && p.indent() == prefix.indent())?;
+    let target = lines[sibling_ix][sibling.quote_end..sibling.marker_end]
+        .chars()
+        .count();
+
+    // An ordered item that starts a new nested list becomes its item 1.
+    let mut cursor = cursor;
+    let starts_list = !lines[sibling_ix + 1..cursor.line]
+        .iter()
+        .any(|line| Prefix::parse(line).marker.is_some());
+    if let (true, Some(marker @ Marker::Ordered { .. })) = (starts_list, prefix.marker) {
+        let old = marker.text();
+        let new = marker.numbered(1).text();
+        let line = &mut lines[cursor.line];
+        line.replace_range(prefix.indent_end..prefix.indent_end + old.len(), &new);
+        cursor.column = (cursor.column + new.len()).saturating_sub(old.len());
+        prefix = Prefix::parse(line);
+    }
+    set_indent(text, lines, cursor, &prefix, target)
+}
+
+/// Shift-Tab: move the list item at the cursor out to its parent's level.
+pub fn outdent(text: &str, cursor: Cursor) -> Option<Edit> {
+    let lines = editing::lines(text);
+    if editing::in_code_block(&lines, cursor.line) {
+        return None;
+    }
+    let prefix = Prefix::parse(lines.get(cursor.line)?);
+    prefix.marker?;
+    if prefix.indent() == 0 {
+        return None;
+    }
+    let target = lines[..cursor.line]
+        .iter()
+        .rev()
+        .map(|line| Prefix::parse(line))
+        .find(|p| p.marker.is_some() && p.indent() < prefix.indent())
+        .map_or(0, |parent| parent.indent());
+    set_indent(text, lines, cursor, &prefix, target)
+}
+
+/// Re-indent the item at the cursor to `target` spaces, keeping the cursor
+/// on the same text.
+fn set_indent(
+    text: &str,
+    mut lines: Vec<String>,
+    cursor: Cursor,
+    prefix: &Prefix,
+    target: usize,
+) -> Option<Edit> {
+    let line = &lines[cursor.line];
+    let old = line[prefix.quote_end..prefix.indent_end].chars().count();
+    let indented = format!(
+        "{}{}{}",
+        &line[..prefix.quote_end],
+        " ".repeat(target),
+        &line[prefix.indent_end..]
+    );
+    lines[cursor.line] = indented;
+    let cursor = Cursor {
+        line: cursor.line,
+        column: (cursor.column + target).saturating_sub(old),
+    };
+    Some(renumbered(text, lines, cursor))
+}
+
+/// Toggle the task box of the list item at the cursor, adding one to a
+/// plain item, or turning a plain line into a task item.
+pub fn toggle_task(text: &str, cursor: Cursor) -> Option<Edit> {
+    let mut lines = editing::lines(text);
+    if editing::in_code_block(&lines, cursor.line) {
+        return None;
+    }
+    let line = lines.get(cursor.line)?.clone();
+    let prefix = Prefix::parse(&line);
+    let (toggled, inserted) = match (prefix.marker, prefix.task) {
+        (Some(_), Some(_)) => (toggle_task_line(&line)?, 0),
+        (Some(_), None) => (
+            format!(
+                "{}[ ] {}",
+                &line[..prefix.marker_end],
+                &line[prefix.marker_end..]
+            ),
+            4,
+        ),
+        (None, _) => (
+            format!(
+                "{}- [ ] {}",
+                &line[..prefix.indent_end],
+                &line[prefix.indent_end..]
+            ),
+            6,
+        ),
+    };
+    lines[cursor.line] = toggled;
+    let cursor = Cursor {
+        line: cursor.line,
+        column: cursor.column + inserted,
+    };
+    Some(Edit::diff(text, &editing::join(&lines), cursor))
+}
+
+/// `line` with its task box toggled, or `None` if it is not a task item.
+pub fn toggle_task_line(line: &str) -> Option<String> {
+    let (checked, at) = Prefix::parse(line).task?;
+    let mark = if checked { " " } else { "x" };
+    let mut toggled = line.to_string();
+    toggled.replace_range(at + 1..at + 2, mark);
+    Some(toggled)
+}
+
+/// An ordered list found by `renumbered`.
+struct OrderedList {
+    /// Line and marker of each item.
+    items: Vec<(usize, Marker)>,
+    /// Last line of the list, nested content included.
+    end: usize,
+}
+
+/// Renumber the ordered lists around the cursor in `lines`, the edited
+/// lines of `text`, so each counts up from its first item, shifting the
+/// cursor if its line's number changed width. Lists numbering every item
+/// the same (`1.`, `1.`, `1.`) keep doing so; other lists are left alone.
+fn renumbered(text: &str, mut lines: Vec<String>, mut cursor: Cursor) -> Edit {
+    let mut lists: Vec<OrderedList> = Vec::new();
+    // Lists still open: indentation, blockquote markers, index in `lists`.
+    let mut open: Vec<(usize, String, usize)> = Vec::new();
+    let mut fence = None;
+    for (ix, line) in lines.iter().enumerate() {
+        if let Some(marker) = editing::fence(line) {
+            fence = match fence {
+                Some(open) if open == marker => None,
+                None => Some(marker),
+                open => open,
+            };
+            continue;
+        }
+        if fence.is_some() || line.trim().is_empty() {
+            continue;
+        }
+        let prefix = Prefix::parse(line);
+        let quote = line[..prefix.quote_end].trim().to_string();
+        let indent = prefix.indent();
+        // A line closes the lists nested deeper, and other lines than an
+        // ordered item close the list at its own indentation too.
+        let ordered = matches!(prefix.marker, Some(Marker::Ordered { .. }));
+        open.retain(|(i, q, _)| *q == quote && (*i < indent || (ordered && *i == indent)));
+        for &(_, _, list) in &open {
+            lists[list].end = ix;
+        }
+        let Some(marker @ Marker::Ordered { .. }) = prefix.marker else {
+            continue;
+        };
+        let list = match open.iter().find(|(i, _, _)| *i == indent) {
+            Some(&(_, _, list)) => list,
+            None => {
+                lists.push(OrderedList {
+                    items: Vec::new(),
+                    end: ix,
+                });
+                open.push((indent, quote, lists.len() - 1));
+                lists.len() - 1
+            }
+        };
+        lists[list].items.push((ix, marker));
+        lists[list].end = ix;
+    }
+
+    let number = |marker: Marker| match marker {
+        Marker::Ordered { number, .. } => number,
+        Marker::Bullet(_) => 0,
+    };
+    for list in &lists {
+        if !(list.items[0].0..=list.end).contains(&cursor.line) {
+            continue;
+        }
+        // The item at the cursor may be new, so it does not decide whether
+        // the list is lazy.
+        let others: Vec<u64> = list
+            .items
+            .iter()
+            .filter(|(ix, _)| *ix != cursor.line)
+            .map(|&(_, marker)| number(marker))
+            .collect();
+        let lazy = others.len() >= 2 && others.iter().all(|&n| n == others[0]);
+        let first = number(list.items[0].1);
+        for (position, &(ix, marker)) in list.items.iter().enumerate() {
+            let expected = if lazy {
+                others[0]
+            } else {
+                first + position as u64
+            };
+            if expected == number(marker) {
+                continue;
+            }
+            let (old, new) = (marker.text(), marker.numbered(expected).text());
+            let line = &mut lines[ix];
+            let start = Prefix::parse(line).indent_end;
+            line.replace_range(start..start + old.len(), &new);
+            if ix == cursor.line && cursor.column > start {
+                cursor.column = (cursor.column + new.len()).saturating_sub(old.len());
+            }
+        }
+    }
+    Edit::diff(text, &editing::join(&lines), cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `command` with the cursor at `|` in `text`, and return the result
+    /// with `|` at the new cursor.
+    fn run(command: fn(&str, Cursor) -> Option<Edit>, text: &str) -> Option<String> {
+        let at = text.find('|').unwrap();
+        let before = &text[..at];
+        let cursor = Cursor {
+            line: before.matches('\n').count(),
+            column: before.rsplit('\n').next().unwrap().chars().count(),
+        };
+        let text = text.replacen('|', "", 1);
+        let edit = command(&text, cursor)?;
+        let mut lines = editing::lines(&edit.apply(&text));
+        let line = &mut lines[edit.cursor.line];
+        line.insert(editing::byte_index(line, edit.cursor.column), '|');
+        Some(editing::join(&lines))
+    }
+
+    #[test]
+    fn newline_continues_items() {
+        assert_eq!(run(newline, "- a|").unwrap(), "- a\n- |");
+        assert_eq!(run(newline, "  * a|b").unwrap(), "  * a\n  * |b");
+        assert_eq!(run(newline, "1. a|\n2. b").unwrap(), "1. a\n2. |\n3. b");
+        assert_eq!(run(newline, "3) a|").unwrap(), "3) a\n4) |");
+        assert_eq!(run(newline, "- [x] a|").unwrap(), "- [x] a\n- [ ] |");
+        assert_eq!(run(newline, "> quote|").unwrap(), "> quote\n> |");
+        assert_eq!(run(newline, "> - a|").unwrap(), "> - a\n> - |");
+    }
+
+    #[test]
+    fn newline_keeps_zero_padding() {
+        assert_eq!(run(newline, "01. a|").unwrap(), "01. a\n02. |");
+        assert_eq!(run(newline, "09. a|").unwrap(), "09. a\n10. |");
+    }
+
+    #[test]
+    fn newline_on_an_empty_item_ends_the_list() {
+        assert_eq!(run(newline, "- a\n- |").unwrap(), "- a\n|");
+        assert_eq!(run(newline, "> a\n> |").unwrap(), "> a\n|");
+        assert_eq!(run(newline, "1. a\n2. |\n3. b").unwrap(), "1. a\n|\n2. b");
+    }
+
+    #[test]
+    fn newline_elsewhere() {
+        assert_eq!(run(newline, "plain|"), None);
+        assert_eq!(run(newline, "```\n- a|\n```"), None);
+        assert_eq!(
+            run(newline, "~~~\n```\n~~~\n- a|").unwrap(),
+            "~~~\n```\n~~~\n- a\n- |"
+        );
+        // Before the text of the item.
+        assert_eq!(run(newline, "-| a"), None);
+    }
+
+    #[test]
+    fn only_the_list_at_the_cursor_is_renumbered() {
+        assert_eq!(
+            run(newline, "1. a|\n2. b\n\ntext\n\n5. x\n9. y").unwrap(),
+            "1. a\n2. |\n3. b\n\ntext\n\n5. x\n9. y"
+        );
+        assert_eq!(
+            run(newline, "```\n1. x\n1. y\n```\n1. a|").unwrap(),
+            "```\n1. x\n1. y\n```\n1. a\n2. |"
+        );
+    }
+
+    #[test]
+    fn lazy_numbering_is_kept() {
+        assert_eq!(
+            run(newline, "1. a\n1. b|\n1. c").unwrap(),
+            "1. a\n1. b\n1. |\n1. c"
+        );
+    }
+
+    #[test]
+    fn indent_nests_under_the_previous_item() {
+        assert_eq!(run(indent, "- a\n- |b").unwrap(), "- a\n  - |b");
+        assert_eq!(run(indent, "10. a\n11. |b").unwrap(), "10. a\n    1. |b");
+        assert_eq!(
+            run(indent, "1. a\n2. |b\n3. c").unwrap(),
+            "1. a\n   1. |b\n2. c"
+        );
+        assert_eq!(
+            run(indent, "1. a\n   1. b\n2. |c").unwrap(),
+            "1. a\n   1. b\n   2. |c"
+        );
+    }
+
+    #[test]
+    fn indent_needs_a_previous_item() {
+        assert_eq!(run(indent, "- |a"), None);
+        assert_eq!(run(indent, "- a\n\n|text"), None);
+    }
+
+    #[test]
+    fn outdent_moves_to_the_parent_level() {
+        assert_eq!(run(outdent, "- a\n  - |b").unwrap(), "- a\n- |b");
+        assert_eq!(run(outdent, "1. a\n   1. |b").unwrap(), "1. a\n2. |b");
+        assert_eq!(run(outdent, "- |a"), None);
+        assert_eq!(run(outdent, "  text|"), None);
+    }
+
+    #[test]
+    fn toggle_task_items() {
+        assert_eq!(run(toggle_task, "- [ ] a|").unwrap(), "- [x] a|");
+        assert_eq!(run(toggle_task, "> 1. [X] |a").unwrap(), "> 1. [ ] |a");
+        assert_eq!(run(toggle_task, "- a|").unwrap(), "- [ ] a|");
+        assert_eq!(run(toggle_task, "  a|").unwrap(), "  - [ ] a|");
+        assert_eq!(run(toggle_task, "```\n- [ ] a|\n```"), None);
+    }
+
+    #[test]
+    fn toggle_task_line_needs_a_task() {
+        assert_eq!(toggle_task_line("* [x] done").unwrap(), "* [ ] done");
+        assert_eq!(toggle_task_line("- [ ]").unwrap(), "- [x]");
+        assert_eq!(toggle_task_line("- [ ]a"), None);
+        assert_eq!(toggle_task_line("[ ] a"), None);
+    }
+}
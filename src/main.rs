@@ -4,65 +4,197 @@ use std::fs;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use futures::StreamExt as _;
 use gpui::*;
-use gpui_component::Root;
+use gpui_component::{Root, Theme};
+
+use appearance::Appearance;
+use documents::DocumentRegistry;
+use open_requests::OpenRequest;
+use session::{RecentFiles, Session, SessionWindow};
+use settings::Settings;
+use views::workspace::{self, Workspace};
 
 mod app;
+mod appearance;
 mod assets;
+mod cli;
+mod documents;
+mod editing;
+mod find;
+mod format;
+mod fuzzy;
 mod http;
+mod i18n;
+mod images;
+mod keymap;
+mod links;
+mod lint;
+mod lists;
+mod markup;
+mod menus;
+mod open_requests;
+mod outline;
+mod paths;
 mod render;
+mod search;
+mod session;
+mod settings;
 mod slidev;
 mod state;
+mod table;
+mod themes;
+mod url;
 mod views;
 
-actions!(markzap, [OpenFile, Quit]);
+actions!(
+    markzap,
+    [
+        OpenFile,
+        QuickOpen,
+        ClearRecentFiles,
+        RestoreSession,
+        ExportHtml,
+        Find,
+        SearchInFolder,
+        CommandPalette,
+        ToggleMode,
+        ToggleProblems,
+        CheckLinks,
+        FormatDocument,
+        Newline,
+        Indent,
+        Outdent,
+        PasteMarkdown,
+        ToggleTask,
+        ToggleBold,
+        ToggleItalic,
+        ToggleCode,
+        InsertLink,
+        CycleHeading,
+        ToggleQuote,
+        ToggleCodeBlock,
+        AddTableRow,
+        RemoveTableRow,
+        AddTableColumn,
+        RemoveTableColumn,
+        SortTable,
+        StartPresentation,
+        GoToHeading,
+        ZoomIn,
+        ZoomOut,
+        ResetZoom,
+        CloseTab,
+        NextTab,
+        PreviousTab,
+        OpenSettings,
+        Quit
+    ]
+);
 
-/// Convert a file:// URL string to a PathBuf.
-fn url_to_path(url: &str) -> Option<PathBuf> {
-    let path_str = url.strip_prefix("file://")?;
-    let decoded = percent_decode(path_str);
-    Some(PathBuf::from(decoded))
+/// Reopen a file from the File > Open Recent menu.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = markzap, no_json)]
+pub struct OpenRecent {
+    pub path: PathBuf,
 }
 
-/// Simple percent-decoding for file paths (e.g. %20 -> space).
-fn percent_decode(input: &str) -> String {
-    let mut result = Vec::new();
-    let bytes = input.as_bytes();
-    let mut i = 0;
-    while i < bytes.len() {
-        if bytes[i] == b'%' && i + 2 < bytes.len() {
-            if let Ok(byte) = u8::from_str_radix(&input[i + 1..i + 3], 16) {
-                result.push(byte);
-                i += 3;
-                continue;
-            }
-        }
-        result.push(bytes[i]);
-        i += 1;
+/// Force light or dark mode, or follow the OS appearance.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = markzap, no_json)]
+pub struct SelectAppearance {
+    pub appearance: Appearance,
+}
+
+/// Switch the UI language (`auto` follows the OS locale).
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = markzap, no_json)]
+pub struct SelectLanguage {
+    pub tag: String,
+}
+
+/// Switch the preview theme (built-in id or user theme name).
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = markzap, no_json)]
+pub struct SelectPreviewTheme {
+    pub id: String,
+}
+
+/// A command-line argument as an open request: `file:` URIs (desktop
+/// launchers pass `%U`) and `markzap://open` deep links are parsed, anything
+/// else is taken as a path.
+fn request_from_arg(arg: &str) -> Option<OpenRequest> {
+    if !url::is_url(arg) {
+        return Some(PathBuf::from(arg).into());
     }
-    String::from_utf8_lossy(&result).into_owned()
+    url::parse_open_url(arg)
+        .inspect_err(|e| eprintln!("Ignoring {:?}: {}", arg, e))
+        .ok()
 }
 
-fn load_file(path: &PathBuf) -> String {
+fn load_file(path: &PathBuf, cx: &App) -> String {
     fs::read_to_string(path).unwrap_or_else(|e| {
         eprintln!("Error reading file {:?}: {}", path, e);
+        let t = i18n::tr(cx);
         format!(
-            "# Error\n\nCould not read `{}`:\n\n```\n{}\n```",
+            "# {}\n\n{} `{}`:\n\n```\n{}\n```",
+            t.read_error_title,
+            t.read_error_body,
             path.display(),
             e
         )
     })
 }
 
+/// Open a window for `path`, or focus the window that already shows it.
 fn open_window(
     content: String,
     path: Option<PathBuf>,
     cx: &mut App,
 ) -> Option<(AnyWindowHandle, Entity<app::AppView>)> {
-    let title = match path.as_ref().and_then(|p| p.file_name()) {
-        Some(name) => format!("MarkZap — {}", name.to_string_lossy()),
-        None => "MarkZap".to_string(),
-    };
+    open_window_with(content, path, None, cx)
+}
+
+/// Open (or focus) the requested document and reveal the requested line.
+fn open_request(
+    request: OpenRequest,
+    cx: &mut App,
+) -> Option<(AnyWindowHandle, Entity<app::AppView>)> {
+    let content = load_file(&request.path, cx);
+    let (window, view) = open_window(content, Some(request.path), cx)?;
+    if let Some(line) = request.line {
+        window
+            .update(cx, |_, window, cx| {
+                view.update(cx, |this, cx| this.go_to_line(line, window, cx));
+            })
+            .ok();
+    }
+    Some((window, view))
+}
+
+/// Like `open_window`, optionally restoring the placement, mode and scroll
+/// position saved in a previous session.
+fn open_window_with(
+    content: String,
+    path: Option<PathBuf>,
+    saved: Option<&SessionWindow>,
+    cx: &mut App,
+) -> Option<(AnyWindowHandle, Entity<app::AppView>)> {
+    let path = path.map(|p| documents::canonical_path(&p));
+    if let Some(ref path) = path {
+        RecentFiles::add(path, cx);
+    }
+    if let Some((window, view)) = path.as_ref().and_then(|p| DocumentRegistry::focus(p, cx)) {
+        workspace::reveal(window, &view, cx);
+        return Some((window, view));
+    }
+
+    let open_in_tabs = Settings::get(cx).open_in_tabs;
+    if open_in_tabs {
+        if let Some((window, workspace)) = workspace::target(cx) {
+            return open_tab(content, path, saved, window, workspace, cx);
+        }
+    }
 
     let app_view: Rc<RefCell<Option<Entity<app::AppView>>>> = Rc::new(RefCell::new(None));
     let app_view_capture = app_view.clone();
@@ -71,119 +203,266 @@ fn open_window(
         .open_window(
             WindowOptions {
                 titlebar: Some(TitlebarOptions {
-                    title: Some(title.into()),
+                    title: Some(title(path.as_deref()).into()),
                     ..Default::default()
                 }),
                 is_resizable: true,
-                window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
-                    None,
-                    size(
-                        px(720.),
-                        cx.primary_display()
-                            .map(|display| display.bounds().size.height)
-                            .unwrap_or_else(|| px(1080.)),
-                    ),
-                    cx,
-                ))),
+                window_bounds: Some(
+                    saved
+                        .and_then(|saved| saved.bounds)
+                        .map(Into::into)
+                        .unwrap_or_else(|| {
+                            WindowBounds::Windowed(Bounds::centered(
+                                None,
+                                size(
+                                    px(Settings::get(cx).window_width),
+                                    cx.primary_display()
+                                        .map(|display| display.bounds().size.height)
+                                        .unwrap_or_else(|| px(1080.)),
+                                ),
+                                cx,
+                            ))
+                        }),
+                ),
                 ..Default::default()
             },
             |window, cx| {
-                let view = cx.new(|_| app::AppView::new(content, path));
+                let view = cx.new(|cx| {
+                    let mut view = app::AppView::new(content, path.clone(), cx);
+                    if let Some(saved) = saved {
+                        view.restore(saved);
+                    }
+                    view
+                });
                 *app_view_capture.borrow_mut() = Some(view.clone());
-                cx.new(|cx| Root::new(view, window, cx))
+                if open_in_tabs {
+                    let workspace = cx.new(|cx| Workspace::new(view, window, cx));
+                    cx.new(|cx| Root::new(workspace, window, cx))
+                } else {
+                    cx.new(|cx| Root::new(view, window, cx))
+                }
             },
         )
         .ok()?;
 
     let view = app_view.borrow_mut().take()?;
+    let window_handle: AnyWindowHandle = window_handle.into();
+    DocumentRegistry::register_window(window_handle, cx);
+    if let Some(ref path) = path {
+        DocumentRegistry::register(path, window_handle, &view, cx);
+    }
 
     // Quit the app only when the last window is closed, and flush pending saves
-    let view_for_close = view.clone();
     window_handle
         .update(cx, |_, window, cx| {
+            view.update(cx, |this, cx| this.observe_window(window, cx));
+            window.focus(&view.focus_handle(cx));
             window.on_window_should_close(cx, move |_, cx| {
-                view_for_close.update(cx, |this, _cx| {
-                    this.save_to_file();
-                });
-                // Count remaining windows (including this one, which hasn't closed yet)
-                if cx.windows().len() <= 1 {
-                    cx.quit();
-                }
+                documents::close_window(window_handle, cx);
                 true
             });
         })
         .ok();
-    Some((window_handle.into(), view))
+    Some((window_handle, view))
+}
+
+/// Add a document as a new tab of a tabbed window.
+fn open_tab(
+    content: String,
+    path: Option<PathBuf>,
+    saved: Option<&SessionWindow>,
+    window: AnyWindowHandle,
+    workspace: Entity<Workspace>,
+    cx: &mut App,
+) -> Option<(AnyWindowHandle, Entity<app::AppView>)> {
+    let view = cx.new(|cx| {
+        let mut view = app::AppView::new(content, path.clone(), cx);
+        if let Some(saved) = saved {
+            view.restore(saved);
+        }
+        view
+    });
+    if let Some(ref path) = path {
+        DocumentRegistry::register(path, window, &view, cx);
+    }
+    window
+        .update(cx, |_, window, cx| {
+            view.update(cx, |this, cx| this.observe_window(window, cx));
+            workspace.update(cx, |workspace, cx| {
+                workspace.add_tab(view.clone(), window, cx)
+            });
+            window.activate_window();
+        })
+        .ok()?;
+    Some((window, view))
+}
+
+/// Title of a window showing the document at `path`.
+fn title(path: Option<&std::path::Path>) -> String {
+    match path.and_then(|p| p.file_name()) {
+        Some(name) => format!("MarkZap — {}", name.to_string_lossy()),
+        None => "MarkZap".to_string(),
+    }
+}
+
+/// Reopen the document windows that were open when MarkZap last quit.
+fn restore_session(cx: &mut App) -> bool {
+    let session = Session::load();
+    let mut restored = false;
+    for saved in &session.windows {
+        if !saved.path.exists() {
+            continue;
+        }
+        let content = load_file(&saved.path, cx);
+        restored |= open_window_with(content, Some(saved.path.clone()), Some(saved), cx).is_some();
+    }
+    restored
 }
 
 fn main() {
-    // Parse CLI: first argument is the .md file path
-    let args: Vec<String> = env::args().collect();
-    let cli_file_path = args.get(1).map(PathBuf::from);
+    // Headless subcommands (`markzap lint ...`) exit without opening a window.
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    // Parse CLI: every argument is a .md file path or URL.
+    let cli_requests: Vec<OpenRequest> = args
+        .iter()
+        .filter_map(|arg| request_from_arg(arg))
+        .collect();
+
+    // A MarkZap instance is already running: hand the files over and exit.
+    if open_requests::forward_to_running_instance(&cli_requests) {
+        return;
+    }
 
-    // Shared buffer for file URLs received via macOS open events (double-click on .md).
-    // macOS sends application:openURLs: which may arrive before applicationDidFinishLaunching,
-    // so we buffer them here and drain in the run() closure.
-    let pending_urls: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
-    let pending_for_callback = pending_urls.clone();
+    // All open requests (macOS open events, CLI, IPC, the Open dialog) go
+    // through one channel. macOS sends application:openURLs: which may arrive
+    // before applicationDidFinishLaunching, so they simply queue up until the
+    // dispatcher starts in the run() closure.
+    let (open_tx, mut open_rx) = open_requests::channel();
+    for request in cli_requests {
+        open_tx.unbounded_send(request).ok();
+    }
 
     let app = Application::new()
         .with_assets(assets::Assets)
         .with_http_client(http::SimpleHttpClient::new());
 
     // Register the open-urls handler BEFORE run().
-    // This captures file:// URLs from macOS document open events.
-    // URLs are buffered and picked up either at launch or by a polling timer.
+    // This captures file:// and markzap:// URLs from document open events on
+    // platforms that deliver them to the running app (macOS); Linux passes
+    // them as arguments.
+    let open_tx_for_urls = open_tx.clone();
     app.on_open_urls(move |urls: Vec<String>| {
-        pending_for_callback.borrow_mut().extend(urls);
+        for url in urls {
+            match url::parse_open_url(&url) {
+                Ok(request) => {
+                    open_tx_for_urls.unbounded_send(request).ok();
+                }
+                Err(e) => eprintln!("Ignoring {:?}: {}", url, e),
+            }
+        }
     });
 
     app.run(move |cx: &mut App| {
         gpui_component::init(cx);
 
-        cx.set_menus(vec![Menu {
-            name: "File".into(),
-            items: vec![
-                MenuItem::action("Open\u{2026}", OpenFile),
-                MenuItem::separator(),
-                MenuItem::action("Quit MarkZap", Quit),
-            ],
-        }]);
+        cx.set_global(Settings::load());
+        Settings::watch(cx);
+        appearance::apply(None, cx);
+
+        // The menus list recent files and the current theme, so rebuild
+        // them when either changes.
+        cx.set_global(RecentFiles::load());
+        cx.set_menus(menus::build(cx));
+        cx.observe_global::<RecentFiles>(|cx| cx.set_menus(menus::build(cx)))
+            .detach();
+        cx.observe_global::<Settings>(|cx| {
+            cx.set_menus(menus::build(cx));
+            appearance::apply(None, cx);
+        })
+        .detach();
+        // Document views re-theme themselves; presentations have no view.
+        cx.observe_global::<Theme>(views::presentation::retheme_presentations)
+            .detach();
 
-        cx.bind_keys([
-            KeyBinding::new("cmd-o", OpenFile, None),
-            KeyBinding::new("cmd-q", Quit, None),
-        ]);
+        keymap::load(cx);
 
         cx.on_action(|_: &Quit, cx| {
+            Session::save(cx);
             cx.quit();
         });
 
-        // Check for URLs received via macOS open events (e.g. double-click on .md file)
-        let open_event_urls: Vec<String> = pending_urls.borrow_mut().drain(..).collect();
-
-        let result = if let Some(path) = open_event_urls.first().and_then(|u| url_to_path(u)) {
-            // Opened via macOS file association
-            let content = load_file(&path);
-            open_window(content, Some(path), cx)
-        } else if let Some(ref path) = cli_file_path {
-            // Opened via CLI argument
-            let content = load_file(path);
-            open_window(content, cli_file_path.clone(), cx)
-        } else {
+        cx.on_action(|_: &ClearRecentFiles, cx| {
+            RecentFiles::clear(cx);
+        });
+
+        cx.on_action(|_: &RestoreSession, cx| {
+            restore_session(cx);
+        });
+
+        cx.on_action(|action: &SelectPreviewTheme, cx| {
+            Settings::update(cx, |settings| settings.preview_theme = action.id.clone());
+        });
+
+        cx.on_action(|action: &SelectAppearance, cx| {
+            Settings::update(cx, |settings| settings.appearance = action.appearance);
+        });
+
+        cx.on_action(|action: &SelectLanguage, cx| {
+            Settings::update(cx, |settings| settings.language = action.tag.clone());
+        });
+
+        cx.on_action(|_: &OpenSettings, cx| {
+            views::settings::open_settings_window(cx);
+        });
+
+        // Tabbed windows close their active tab; other document windows
+        // close themselves.
+        cx.on_action(|_: &CloseTab, cx| {
+            let Some(window) = cx.active_window() else {
+                return;
+            };
+            if DocumentRegistry::is_document_window(window, cx) {
+                documents::close_window(window, cx);
+                window
+                    .update(cx, |_, window, _| window.remove_window())
+                    .ok();
+            }
+        });
+
+        // Document windows search their own folder; elsewhere, ask for one.
+        cx.on_action(|_: &SearchInFolder, cx| {
+            views::search::open_search_window(None, cx);
+        });
+
+        // Open whatever was requested before launch (CLI argument or
+        // macOS file association), then the previous session if enabled,
+        // or fall back to the welcome screen.
+        let mut opened_any = false;
+        while let Ok(Some(request)) = open_rx.try_next() {
+            opened_any |= open_request(request, cx).is_some();
+        }
+        if !opened_any && Settings::get(cx).restore_session {
+            opened_any = restore_session(cx);
+        }
+        if !opened_any {
             // No file specified — show welcome screen
-            let content = String::from(
-                "# Welcome to MarkZap\n\n\
-                 Open a `.md` file by passing it as a command-line argument:\n\n\
-                 ```\n\
-                 markzap path/to/file.md\n\
-                 ```\n\n\
-                 Or double-click a `.md` file to open it with MarkZap.",
-            );
-            open_window(content, None, cx)
-        };
+            let content = i18n::tr(cx).welcome.to_string();
+            open_window(content, None, cx);
+        }
+
+        let open_tx_for_recent = open_tx.clone();
+        cx.on_action(move |action: &OpenRecent, _| {
+            open_tx_for_recent
+                .unbounded_send(action.path.clone().into())
+                .ok();
+        });
 
         // Register the OpenFile action (Cmd-O) — opens file in a new window
+        let open_tx_for_action = open_tx.clone();
         cx.on_action(move |_: &OpenFile, cx| {
             let receiver = cx.prompt_for_paths(PathPromptOptions {
                 files: true,
@@ -191,45 +470,31 @@ fn main() {
                 multiple: false,
                 prompt: None,
             });
-            cx.spawn(async move |cx| {
+            let open_tx = open_tx_for_action.clone();
+            cx.spawn(async move |_| {
                 if let Ok(Ok(Some(paths))) = receiver.await {
                     if let Some(path) = paths.into_iter().next() {
-                        cx.update(|cx| {
-                            let content = load_file(&path);
-                            open_window(content, Some(path), cx);
-                        })
-                        .ok();
+                        open_tx.unbounded_send(path.into()).ok();
                     }
                 }
             })
             .detach();
         });
 
-        // Poll for new open-url events that arrive after launch.
-        // macOS on_open_urls doesn't give us access to cx, so we poll the buffer.
-        let pending_urls_poll = pending_urls.clone();
+        cx.set_global(open_requests::OpenRequests(open_tx.clone()));
+
+        // Later invocations of `markzap file.md` forward their path over IPC.
+        open_requests::listen_for_ipc(open_tx.clone());
+
+        // Dispatch open requests as they arrive; the task sleeps until then.
         cx.spawn(async move |cx| {
-            loop {
-                cx.background_executor()
-                    .timer(std::time::Duration::from_millis(100))
-                    .await;
-                let urls: Vec<String> = pending_urls_poll.borrow_mut().drain(..).collect();
-                if !urls.is_empty() {
-                    for url in urls {
-                        if let Some(path) = url_to_path(&url) {
-                            let content = load_file(&path);
-                            cx.update(|cx| {
-                                open_window(content, Some(path), cx);
-                            })
-                            .ok();
-                        }
-                    }
-                }
+            while let Some(request) = open_rx.next().await {
+                cx.update(|cx| {
+                    open_request(request, cx);
+                })
+                .ok();
             }
         })
         .detach();
-
-        // Ignore the result — we no longer tie actions to a single window
-        let _ = result;
     });
 }
@@ -0,0 +1,325 @@
+use std::ops::Range;
+
+use crate::editing::{self, Cursor, Edit};
+
+/// Inline formatting that wraps the selection in a delimiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inline {
+    Bold,
+    Italic,
+    Code,
+}
+
+impl Inline {
+    fn delimiter(self) -> &'static str {
+        match self {
+            Self::Bold => "**",
+            Self::Italic => "*",
+            Self::Code => "`",
+        }
+    }
+
+    /// Whether a run of `len` delimiter characters around text is this
+    /// formatting: a pair of `*` is bold, not italic.
+    fn matches_run(self, len: usize) -> bool {
+        match self {
+            Self::Italic => len == 1 || len >= 3,
+            _ => len >= self.delimiter().len(),
+        }
+    }
+}
+
+/// Number of `c` characters at the start (or end) of `text`.
+fn run(text: &str, c: char, at_end: bool) -> usize {
+    if at_end {
+        text.chars().rev().take_while(|&ch| ch == c).count()
+    } else {
+        text.chars().take_while(|&ch| ch == c).count()
+    }
+}
+
+/// The cursor at byte offset `offset` of `text`.
+fn cursor_at(text: &str, offset: usize) -> Cursor {
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |ix| ix + 1);
+    Cursor {
+        line,
+        column: before[line_start..].chars().count(),
+    }
+}
+
+/// The edit turning `old` into `new`, with the cursor at byte offset
+/// `cursor` of `new`.
+fn edit(old: &str, new: String, cursor: usize) -> Edit {
+    Edit::diff(old, &new, cursor_at(&new, cursor))
+}
+
+/// Toggle `format` on the selection (byte offsets): unwrap it if it is
+/// already formatted, inside or just around the selection, wrap it
+/// otherwise. With nothing selected, insert an empty pair and put the
+/// cursor between.
+pub fn toggle_inline(text: &str, selection: Range<usize>, format: Inline) -> Edit {
+    let delimiter = format.delimiter();
+    let c = delimiter.chars().next().unwrap_or('*');
+    let len = delimiter.len();
+    let selected = &text[selection.clone()];
+    let (before, after) = (&text[..selection.start], &text[selection.end..]);
+
+    // `**text**` selected with its delimiters.
+    let inner = selected.len() >= 2 * len
+        && format.matches_run(run(selected, c, false).min(run(selected, c, true)));
+    if inner && !selected.trim_matches(c).is_empty() {
+        let unwrapped = &selected[len..selected.len() - len];
+        let new = format!("{}{}{}", before, unwrapped, after);
+        return edit(text, new, selection.start + unwrapped.len());
+    }
+    // `text` selected between its delimiters.
+    let outer = format.matches_run(run(before, c, true).min(run(after, c, false)));
+    if outer && !selected.is_empty() {
+        let new = format!(
+            "{}{}{}",
+            &before[..before.len() - len],
+            selected,
+            &after[len..]
+        );
+        return edit(text, new, selection.end - len);
+    }
+
+    let new = format!("{}{}{}{}{}", before, delimiter, selected, delimiter, after);
+    let cursor = if selected.is_empty() {
+        selection.start + len
+    } else {
+        selection.end + 2 * len
+    };
+    edit(text, new, cursor)
+}
+
+/// Make the selection a link. Selected text becomes the label, with the
+/// cursor left in the empty destination; a selected URL becomes the
+/// destination, with the cursor in the empty label.
+pub fn link(text: &str, selection: Range<usize>) -> Edit {
+    let selected = &text[selection.clone()];
+    let (before, after) = (&text[..selection.start], &text[selection.end..]);
+    let is_url = selected.starts_with("http://") || selected.starts_with("https://");
+    if is_url {
+        let new = format!("{}[]({}){}", before, selected, after);
+        edit(text, new, selection.start + 1)
+    } else {
+        let new = format!("{}[{}](){}", before, selected, after);
+        edit(text, new, selection.start + selected.len() + 3)
+    }
+}
+
+/// Lines touched by the selection, as a range of zero-based line numbers.
+fn selected_lines(text: &str, selection: &Range<usize>) -> Range<usize> {
+    let first = cursor_at(text, selection.start).line;
+    let mut last = cursor_at(text, selection.end).line;
+    // A selection ending at the start of a line does not include it.
+    if last > first && text[..selection.end].ends_with('\n') {
+        last -= 1;
+    }
+    first..last + 1
+}
+
+/// Cycle the heading level of the line at the cursor: text, `#`, `##`, ...
+/// `######`, then back to text.
+pub fn cycle_heading(text: &str, selection: Range<usize>) -> Option<Edit> {
+    let mut lines = editing::lines(text);
+    let line = cursor_at(text, selection.end).line;
+    if editing::in_code_block(&lines, line) {
+        return None;
+    }
+    let current = &lines[line];
+    let hashes = run(current, '#', false);
+    let level = match current[hashes..].chars().next() {
+        None | Some(' ') if (1..=6).contains(&hashes) => hashes,
+        _ => 0,
+    };
+    let body = current[level..].trim_start();
+    let old_prefix = current.len() - body.len();
+    let prefix = match level {
+        6 => String::new(),
+        _ => format!("{} ", "#".repeat(level + 1)),
+    };
+    // Keep the cursor on the same text.
+    let line_start = line_offset(text, line);
+    let column = prefix.len() + (selection.end - line_start).saturating_sub(old_prefix);
+    lines[line] = format!("{}{}", prefix, body);
+    let new = editing::join(&lines);
+    Some(edit(text, new, line_start + column))
+}
+
+/// Toggle a blockquote on the selected lines: remove one `>` level if they
+/// are all quoted, quote them otherwise.
+pub fn toggle_quote(text: &str, selection: Range<usize>) -> Option<Edit> {
+    let mut lines = editing::lines(text);
+    let range = selected_lines(text, &selection);
+    if editing::in_code_block(&lines, range.start) {
+        return None;
+    }
+    let quoted = lines[range.clone()]
+        .iter()
+        .all(|line| line.trim_start().starts_with('>'));
+    for line in &mut lines[range.clone()] {
+        if quoted {
+            let start = line.len() - line.trim_start().len();
+            let mut end = start + 1;
+            if line[end..].starts_with(' ') {
+                end += 1;
+            }
+            line.replace_range(start..end, "");
+        } else {
+            line.insert_str(0, "> ");
+        }
+    }
+    let new = editing::join(&lines);
+    // The cursor goes to the end of the last quoted line.
+    let end = line_offset(&new, range.end - 1) + lines[range.end - 1].len();
+    Some(edit(text, new, end))
+}
+
+/// Wrap the selected lines in a fenced code block, or remove the fences if
+/// the selection is one. The cursor goes after the opening fence, where the
+/// language is written.
+pub fn toggle_code_block(text: &str, selection: Range<usize>) -> Edit {
+    let mut lines = editing::lines(text);
+    let range = selected_lines(text, &selection);
+    let is_fence = |line: &String| editing::fence(line).is_some();
+    let fenced =
+        range.len() >= 2 && is_fence(&lines[range.start]) && is_fence(&lines[range.end - 1]);
+    if fenced {
+        lines.remove(range.end - 1);
+        lines.remove(range.start);
+        let new = editing::join(&lines);
+        let offset = line_offset(&new, range.start);
+        return edit(text, new, offset);
+    }
+    lines.insert(range.end, "```".to_string());
+    lines.insert(range.start, "```".to_string());
+    let new = editing::join(&lines);
+    let offset = line_offset(&new, range.start) + 3;
+    edit(text, new, offset)
+}
+
+/// Byte offset of the start of zero-based `line` in `text`.
+fn line_offset(text: &str, line: usize) -> usize {
+    text.split('\n')
+        .take(line)
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        .min(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `command` on `text` with the selection between `«` and `»`, and
+    /// return the result with `|` at the new cursor.
+    fn run(command: impl FnOnce(&str, Range<usize>) -> Option<Edit>, text: &str) -> Option<String> {
+        let start = text.find('«').unwrap();
+        let end = text.find('»').unwrap() - '«'.len_utf8();
+        let text = text.replace(['«', '»'], "");
+        let edit = command(&text, start..end)?;
+        let mut lines = editing::lines(&edit.apply(&text));
+        let line = &mut lines[edit.cursor.line];
+        line.insert(editing::byte_index(line, edit.cursor.column), '|');
+        Some(editing::join(&lines))
+    }
+
+    fn inline(format: Inline) -> impl FnOnce(&str, Range<usize>) -> Option<Edit> {
+        move |text, selection| Some(toggle_inline(text, selection, format))
+    }
+
+    #[test]
+    fn wraps_the_selection() {
+        assert_eq!(
+            run(inline(Inline::Bold), "a «word» b").unwrap(),
+            "a **word**| b"
+        );
+        assert_eq!(run(inline(Inline::Italic), "«été»").unwrap(), "*été*|");
+        assert_eq!(run(inline(Inline::Code), "«x»").unwrap(), "`x`|");
+    }
+
+    #[test]
+    fn empty_selection_inserts_a_pair() {
+        assert_eq!(run(inline(Inline::Bold), "a «»b").unwrap(), "a **|**b");
+    }
+
+    #[test]
+    fn unwraps_formatted_text() {
+        // Selected with its delimiters, or between them.
+        assert_eq!(
+            run(inline(Inline::Bold), "a «**word**» b").unwrap(),
+            "a word| b"
+        );
+        assert_eq!(
+            run(inline(Inline::Bold), "a **«word»** b").unwrap(),
+            "a word| b"
+        );
+        assert_eq!(run(inline(Inline::Code), "`«x»`").unwrap(), "x|");
+    }
+
+    #[test]
+    fn italic_is_not_bold() {
+        // Inside bold, italic wraps rather than unwraps.
+        assert_eq!(
+            run(inline(Inline::Italic), "**«word»**").unwrap(),
+            "***word*|**"
+        );
+        assert_eq!(run(inline(Inline::Italic), "*«word»*").unwrap(), "word|");
+        assert_eq!(
+            run(inline(Inline::Italic), "***«word»***").unwrap(),
+            "**word|**"
+        );
+    }
+
+    #[test]
+    fn links() {
+        let link = |text: &str, selection| Some(link(text, selection));
+        assert_eq!(run(link, "see «docs»").unwrap(), "see [docs](|)");
+        assert_eq!(
+            run(link, "«https://example.com»").unwrap(),
+            "[|](https://example.com)"
+        );
+        assert_eq!(run(link, "«»").unwrap(), "[](|)");
+    }
+
+    #[test]
+    fn headings_cycle() {
+        assert_eq!(run(cycle_heading, "Ti«»tle").unwrap(), "# Ti|tle");
+        assert_eq!(run(cycle_heading, "# Ti«»tle").unwrap(), "## Ti|tle");
+        assert_eq!(run(cycle_heading, "###### Ti«»tle").unwrap(), "Ti|tle");
+        // Seven hashes are text, not a heading.
+        assert_eq!(run(cycle_heading, "#######«»").unwrap(), "# #######|");
+        assert_eq!(run(cycle_heading, "```\nco«»de\n```"), None);
+    }
+
+    #[test]
+    fn quotes_toggle() {
+        assert_eq!(run(toggle_quote, "«a\nb»\nc").unwrap(), "> a\n> b|\nc");
+        assert_eq!(run(toggle_quote, "> «a\n>b»").unwrap(), "a\nb|");
+        // A mix of quoted and plain lines is quoted.
+        assert_eq!(run(toggle_quote, "«> a\nb»").unwrap(), "> > a\n> b|");
+        // A selection ending at the start of a line leaves that line alone.
+        assert_eq!(run(toggle_quote, "«a\n»b").unwrap(), "> a|\nb");
+    }
+
+    #[test]
+    fn code_blocks_toggle() {
+        let toggle = |text: &str, selection| Some(toggle_code_block(text, selection));
+        assert_eq!(
+            run(toggle, "a\n«b\nc»\nd").unwrap(),
+            "a\n```|\nb\nc\n```\nd"
+        );
+        assert_eq!(run(toggle, "«```\nb\n```»").unwrap(), "|b");
+        assert_eq!(run(toggle, "«~~~\nb\n~~~»").unwrap(), "|b");
+    }
+
+    #[test]
+    fn edits_replace_only_the_change() {
+        let edit = toggle_inline("one two three", 4..7, Inline::Bold);
+        assert_eq!((edit.range, edit.text.as_str()), (4..7, "**two**"));
+    }
+}
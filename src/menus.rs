@@ -0,0 +1,204 @@
+use gpui::*;
+
+use crate::appearance::Appearance;
+use crate::i18n::{self, Language};
+use crate::session::RecentFiles;
+use crate::settings::Settings;
+use crate::themes;
+use crate::{
+    AddTableColumn, AddTableRow, CheckLinks, ClearRecentFiles, CloseTab, CommandPalette,
+    CycleHeading, ExportHtml, Find, FormatDocument, GoToHeading, InsertLink, NextTab, OpenFile,
+    OpenRecent, OpenSettings, PreviousTab, QuickOpen, Quit, RemoveTableColumn, RemoveTableRow,
+    ResetZoom, RestoreSession, SearchInFolder, SelectAppearance, SelectLanguage,
+    SelectPreviewTheme, SortTable, StartPresentation, ToggleBold, ToggleCode, ToggleCodeBlock,
+    ToggleItalic, ToggleMode, ToggleProblems, ToggleQuote, ToggleTask, ZoomIn, ZoomOut,
+};
+
+/// Menu label with a check mark in front of the current choice.
+fn checked_label(label: &str, checked: bool) -> String {
+    if checked {
+        format!("\u{2713} {}", label)
+    } else {
+        label.to_string()
+    }
+}
+
+/// The menu bar: installed as the global menu, and drawn inside document
+/// windows on platforms without one.
+pub fn build(cx: &App) -> Vec<Menu> {
+    let t = i18n::tr(cx);
+    let settings = Settings::get(cx);
+
+    let recent = cx
+        .try_global::<RecentFiles>()
+        .map(|recent| recent.paths().to_vec())
+        .unwrap_or_default();
+    let mut recent_items: Vec<MenuItem> = recent
+        .into_iter()
+        .map(|path| MenuItem::action(path.display().to_string(), OpenRecent { path }))
+        .collect();
+    if !recent_items.is_empty() {
+        recent_items.push(MenuItem::separator());
+    }
+    recent_items.push(MenuItem::action(t.menu_clear_recent, ClearRecentFiles));
+
+    let appearance_items = Appearance::ALL
+        .into_iter()
+        .map(|appearance| {
+            let name = checked_label(appearance.label(t), appearance == settings.appearance);
+            MenuItem::action(name, SelectAppearance { appearance })
+        })
+        .collect();
+
+    let theme_items = themes::available_themes()
+        .into_iter()
+        .map(|(id, label)| {
+            let name = checked_label(&label, id == settings.preview_theme);
+            MenuItem::action(name, SelectPreviewTheme { id })
+        })
+        .collect();
+
+    let mut language_items = vec![MenuItem::action(
+        checked_label(t.language_auto, settings.language == "auto"),
+        SelectLanguage {
+            tag: "auto".to_string(),
+        },
+    )];
+    language_items.extend(Language::ALL.into_iter().map(|language| {
+        MenuItem::action(
+            checked_label(language.native_name(), settings.language == language.tag()),
+            SelectLanguage {
+                tag: language.tag().to_string(),
+            },
+        )
+    }));
+
+    // Tab commands only apply to tabbed windows.
+    let (close_label, tab_items) = if settings.open_in_tabs {
+        let tab_items = vec![
+            MenuItem::action(t.menu_next_tab, NextTab),
+            MenuItem::action(t.menu_previous_tab, PreviousTab),
+            MenuItem::separator(),
+        ];
+        (t.menu_close_tab, tab_items)
+    } else {
+        (t.menu_close_window, Vec::new())
+    };
+
+    vec![
+        Menu {
+            name: t.menu_file.into(),
+            items: vec![
+                MenuItem::action(t.menu_open, OpenFile),
+                MenuItem::action(t.menu_quick_open, QuickOpen),
+                MenuItem::submenu(Menu {
+                    name: t.menu_open_recent.into(),
+                    items: recent_items,
+                }),
+                MenuItem::action(t.menu_reopen_session, RestoreSession),
+                MenuItem::separator(),
+                MenuItem::action(t.menu_export_html, ExportHtml),
+                MenuItem::separator(),
+                // Without tabs, CloseTab closes the window (see `main`).
+                MenuItem::action(close_label, CloseTab),
+                MenuItem::separator(),
+                MenuItem::action(t.menu_settings, OpenSettings),
+                MenuItem::separator(),
+                MenuItem::action(t.menu_quit, Quit),
+            ],
+        },
+        Menu {
+            name: t.menu_edit.into(),
+            items: vec![
+                MenuItem::action(t.menu_find, Find),
+                MenuItem::action(t.menu_search_in_folder, SearchInFolder),
+                MenuItem::separator(),
+                MenuItem::action(t.menu_format_document, FormatDocument),
+                MenuItem::action(t.menu_toggle_task, ToggleTask),
+                MenuItem::submenu(Menu {
+                    name: t.menu_format.into(),
+                    items: vec![
+                        MenuItem::action(t.menu_bold, ToggleBold),
+                        MenuItem::action(t.menu_italic, ToggleItalic),
+                        MenuItem::action(t.menu_code, ToggleCode),
+                        MenuItem::action(t.menu_link, InsertLink),
+                        MenuItem::separator(),
+                        MenuItem::action(t.menu_heading, CycleHeading),
+                        MenuItem::action(t.menu_quote, ToggleQuote),
+                        MenuItem::action(t.menu_code_block, ToggleCodeBlock),
+                    ],
+                }),
+                MenuItem::submenu(Menu {
+                    name: t.menu_table.into(),
+                    items: vec![
+                        MenuItem::action(t.menu_add_row, AddTableRow),
+                        MenuItem::action(t.menu_remove_row, RemoveTableRow),
+                        MenuItem::action(t.menu_add_column, AddTableColumn),
+                        MenuItem::action(t.menu_remove_column, RemoveTableColumn),
+                        MenuItem::separator(),
+                        MenuItem::action(t.menu_sort_table, SortTable),
+                    ],
+                }),
+                MenuItem::action(t.menu_check_links, CheckLinks),
+            ],
+        },
+        Menu {
+            name: t.menu_view.into(),
+            items: vec![
+                MenuItem::action(t.menu_command_palette, CommandPalette),
+                MenuItem::separator(),
+                MenuItem::action(t.menu_toggle_mode, ToggleMode),
+                MenuItem::action(t.menu_present, StartPresentation),
+                MenuItem::action(t.menu_go_to_heading, GoToHeading),
+                MenuItem::action(t.menu_problems, ToggleProblems),
+                MenuItem::separator(),
+                MenuItem::action(t.menu_zoom_in, ZoomIn),
+                MenuItem::action(t.menu_zoom_out, ZoomOut),
+                MenuItem::action(t.menu_reset_zoom, ResetZoom),
+                MenuItem::separator(),
+            ]
+            .into_iter()
+            .chain(tab_items)
+            .chain([
+                MenuItem::submenu(Menu {
+                    name: t.menu_appearance.into(),
+                    items: appearance_items,
+                }),
+                MenuItem::submenu(Menu {
+                    name: t.menu_preview_theme.into(),
+                    items: theme_items,
+                }),
+                MenuItem::submenu(Menu {
+                    name: t.menu_language.into(),
+                    items: language_items,
+                }),
+            ])
+            .collect(),
+        },
+    ]
+}
+
+/// Whether the platform shows `cx.set_menus` itself; elsewhere the menus are
+/// drawn in each document window.
+pub fn has_global_menu() -> bool {
+    cfg!(target_os = "macos")
+}
+
+/// Items of the (sub)menu at `path`: a top-level menu index followed by
+/// submenu indices.
+pub fn items_at(path: &[usize], cx: &App) -> Vec<MenuItem> {
+    let Some((&first, rest)) = path.split_first() else {
+        return Vec::new();
+    };
+    let Some(menu) = build(cx).into_iter().nth(first) else {
+        return Vec::new();
+    };
+    let mut items = menu.items;
+    for &ix in rest {
+        match items.into_iter().nth(ix) {
+            Some(MenuItem::Submenu(menu)) => items = menu.items,
+            _ => return Vec::new(),
+        }
+    }
+    items
+}
@@ -0,0 +1,136 @@
+use std::env;
+use std::path::PathBuf;
+
+use futures::channel::mpsc;
+use gpui::{App, Global};
+
+/// A document to open, optionally scrolled to a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenRequest {
+    pub path: PathBuf,
+    /// Zero-based line to reveal once the document is shown.
+    pub line: Option<usize>,
+}
+
+impl From<PathBuf> for OpenRequest {
+    fn from(path: PathBuf) -> Self {
+        Self { path, line: None }
+    }
+}
+
+/// Sending half of the open-request channel. Cheap to clone and `Send`, so it
+/// can be handed to platform callbacks, actions and the IPC listener thread.
+pub type OpenSender = mpsc::UnboundedSender<OpenRequest>;
+
+/// Receiving half of the open-request channel, drained by the dispatcher task
+/// in `main`, which only wakes up when a request actually arrives.
+pub type OpenReceiver = mpsc::UnboundedReceiver<OpenRequest>;
+
+pub fn channel() -> (OpenSender, OpenReceiver) {
+    mpsc::unbounded()
+}
+
+/// The app-wide open-request sender, for views that open documents.
+pub struct OpenRequests(pub OpenSender);
+
+impl Global for OpenRequests {}
+
+/// Queue `request` for the dispatcher in `main`.
+pub fn send(request: impl Into<OpenRequest>, cx: &App) {
+    if let Some(OpenRequests(sender)) = cx.try_global::<OpenRequests>() {
+        sender.unbounded_send(request.into()).ok();
+    }
+}
+
+/// Location of the per-user socket used to hand paths over to an already
+/// running MarkZap instance.
+fn socket_path() -> PathBuf {
+    let dir = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir);
+    let user = env::var("USER").unwrap_or_else(|_| "default".to_string());
+    dir.join(format!("markzap-{}.sock", user))
+}
+
+/// Try to forward `requests` to a running instance, one `markzap://open`
+/// link per line. Returns `true` if another instance accepted them, in which
+/// case this process should exit.
+#[cfg(unix)]
+pub fn forward_to_running_instance(requests: &[OpenRequest]) -> bool {
+    use std::io::Write as _;
+    use std::os::unix::net::UnixStream;
+
+    if requests.is_empty() {
+        return false;
+    }
+    let Ok(mut stream) = UnixStream::connect(socket_path()) else {
+        return false;
+    };
+    for request in requests {
+        // Send absolute paths: the running instance has its own working directory.
+        let request = OpenRequest {
+            path: std::fs::canonicalize(&request.path).unwrap_or_else(|_| request.path.clone()),
+            line: request.line,
+        };
+        if writeln!(stream, "{}", crate::url::deep_link(&request)).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(not(unix))]
+pub fn forward_to_running_instance(_requests: &[OpenRequest]) -> bool {
+    false
+}
+
+/// Listen for requests sent by later invocations (`markzap file.md` while the
+/// app is already running) and feed them into the open-request channel.
+#[cfg(unix)]
+pub fn listen_for_ipc(sender: OpenSender) {
+    use std::io::{BufRead as _, BufReader};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = socket_path();
+    // A socket nobody answers on is left over from a crashed instance.
+    if path.exists() && UnixStream::connect(&path).is_err() {
+        std::fs::remove_file(&path).ok();
+    }
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Could not listen on {:?}: {}", path, e);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                // Older instances sent bare paths rather than links.
+                let request = if crate::url::is_url(line) {
+                    match crate::url::parse_open_url(line) {
+                        Ok(request) => request,
+                        Err(e) => {
+                            eprintln!("Ignoring {:?}: {}", line, e);
+                            continue;
+                        }
+                    }
+                } else {
+                    PathBuf::from(line).into()
+                };
+                if sender.unbounded_send(request).is_err() {
+                    // The dispatcher is gone: the app is shutting down.
+                    return;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn listen_for_ipc(_sender: OpenSender) {}
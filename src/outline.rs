@@ -0,0 +1,45 @@
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{Arena, parse_document};
+
+use crate::render;
+
+/// A heading of the document, as listed by Go to Heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 to 6.
+    pub level: u8,
+    /// Plain text of the heading, without markup.
+    pub text: String,
+    /// Zero-based line where the heading starts.
+    pub line: usize,
+}
+
+/// Every heading of `markdown`, in document order.
+pub fn headings(markdown: &str) -> Vec<Heading> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, markdown, &render::markdown_options());
+    root.descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            match data.value {
+                NodeValue::Heading(ref heading) => Some(Heading {
+                    level: heading.level,
+                    text: plain_text(node),
+                    line: data.sourcepos.start.line.saturating_sub(1),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Concatenated text and inline code of `node`'s descendants.
+pub fn plain_text<'a>(node: &'a AstNode<'a>) -> String {
+    node.descendants()
+        .filter_map(|child| match child.data.borrow().value {
+            NodeValue::Text(ref text) => Some(text.to_string()),
+            NodeValue::Code(ref code) => Some(code.literal.clone()),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::PathBuf;
+
+use futures::StreamExt as _;
+use futures::channel::mpsc;
+use gpui::App;
+use notify::{RecursiveMode, Watcher as _};
+
+/// Directory holding MarkZap's persisted state (recent files, last session).
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("MarkZap"))
+}
+
+/// Directory holding user-editable configuration such as `settings.toml`.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("MarkZap"))
+}
+
+/// Call `on_change` whenever `file_name` in the config dir changes on disk.
+pub fn watch_config_file(
+    file_name: &'static str,
+    cx: &mut App,
+    on_change: impl Fn(&mut App) + 'static,
+) {
+    let Some(dir) = config_dir() else {
+        return;
+    };
+    // The watcher needs an existing directory, even before the file exists.
+    fs::create_dir_all(&dir).ok();
+
+    let (tx, mut rx) = mpsc::unbounded::<()>();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let touches_file = event
+                .paths
+                .iter()
+                .any(|path| path.file_name().is_some_and(|name| name == file_name));
+            if touches_file {
+                tx.unbounded_send(()).ok();
+            }
+        }
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Could not watch {}: {}", file_name, e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        eprintln!("Could not watch {:?}: {}", dir, e);
+        return;
+    }
+
+    cx.spawn(async move |cx| {
+        // Keep the watcher alive for as long as the task runs.
+        let _watcher = watcher;
+        while rx.next().await.is_some() {
+            if cx.update(|cx| on_change(cx)).is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+}
@@ -1,11 +1,48 @@
 use comrak::{Options, markdown_to_html};
+use serde::Deserialize;
 
-/// Parses Markdown to HTML (via comrak) and wraps it in a self-contained,
-/// themed HTML document suitable for display in a WebView.
-///
-/// The page includes an embedded, offline find bar bound to Cmd/Ctrl+F that
-/// uses `window.find` (Enter = next, Shift+Enter = previous, Esc = close).
-pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
+use crate::i18n::Strings;
+use crate::themes::Palette;
+
+/// Presentation settings for a rendered preview page.
+#[derive(Debug, Clone)]
+pub struct PageOptions {
+    pub dark: bool,
+    pub palette: Palette,
+    /// Extra stylesheet of a user theme, applied after the built-in one.
+    pub custom_css: Option<String>,
+    /// Vertical scroll offset restored once the page has loaded.
+    pub scroll_y: f64,
+    /// Maximum width of the content column, in pixels.
+    pub content_width: u32,
+    /// Text scale factor (1.0 = 16px body text).
+    pub zoom: f32,
+    /// Catalog for the find bar labels.
+    pub strings: &'static Strings,
+    /// Let task list checkboxes be clicked, reporting the toggled line.
+    pub editable_tasks: bool,
+}
+
+/// Messages posted by the preview page through `window.ipc.postMessage`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PreviewMessage {
+    /// The page was scrolled (debounced), reporting the new offset.
+    Scroll { y: f64 },
+    /// A task checkbox was clicked; `line` is the one-based source line of
+    /// its list item.
+    Task { line: usize },
+}
+
+impl PreviewMessage {
+    pub fn parse(message: &str) -> Option<Self> {
+        serde_json::from_str(message).ok()
+    }
+}
+
+/// The comrak extensions MarkZap renders, shared by everything that parses
+/// documents so they all see the same structure.
+pub fn markdown_options() -> Options<'static> {
     let mut options = Options::default();
     options.extension.table = true;
     options.extension.strikethrough = true;
@@ -13,21 +50,31 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
     options.extension.tasklist = true;
     options.extension.footnotes = true;
     options.extension.superscript = true;
+    // Give headings ids so `#anchor` links work (and can be checked).
+    options.extension.header_ids = Some(String::new());
     // Allow raw HTML embedded in the Markdown (this is a viewer).
     options.render.unsafe_ = true;
+    options
+}
 
+/// Parses Markdown to HTML (via comrak) and wraps it in a self-contained,
+/// themed HTML document suitable for display in a WebView.
+///
+/// The page includes an embedded, offline find bar bound to Cmd/Ctrl+F (or
+/// opened by the app with `OPEN_FIND_SCRIPT`) that highlights every match in
+/// the rendered text, with match count and case/whole-word toggles
+/// (Enter = next, Shift+Enter = previous, Esc = close), and reports its
+/// scroll position back to the app so it can be restored.
+/// With `editable_tasks`, clicking a task checkbox reports its line too.
+/// Colors are CSS variables so `retheme_script` can swap them without a reload.
+pub fn render_markdown_page(markdown: &str, page: PageOptions) -> String {
+    let mut options = markdown_options();
+    // Task items carry their source line for the checkbox clicks.
+    options.render.sourcepos = page.editable_tasks;
     let body = markdown_to_html(markdown, &options);
 
-    // Theme palette
-    let (bg, fg, muted, border, code_bg, code_fg, quote_border, link) = if dark {
-        (
-            "#1e1e1e", "#e6e6e6", "#9a9a9a", "#3a3a3a", "#2a2a2a", "#e6e6e6", "#4a4a4a", "#5aa9e6",
-        )
-    } else {
-        (
-            "#ffffff", "#1f2328", "#656d76", "#d0d7de", "#f4f4f4", "#1f2328", "#d0d7de", "#0969da",
-        )
-    };
+    let palette_css = palette_css(&page);
+    let custom_css = page.custom_css.as_deref().unwrap_or("");
 
     format!(
         r#"<!DOCTYPE html>
@@ -36,25 +83,27 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
 <meta charset="utf-8">
 <meta name="viewport" content="width=device-width, initial-scale=1">
 <title>MarkZap</title>
+<style id="markzap-palette">
+{palette_css}
+</style>
 <style>
-  :root {{ color-scheme: {scheme}; }}
   html, body {{ margin: 0; padding: 0; }}
   body {{
-    background: {bg};
-    color: {fg};
+    background: var(--bg);
+    color: var(--fg);
     font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif;
-    font-size: 16px;
+    font-size: {font_size}px;
     line-height: 1.6;
     -webkit-font-smoothing: antialiased;
   }}
   .markzap-content {{
-    max-width: 860px;
+    max-width: {content_width}px;
     margin: 0 auto;
     padding: 32px 40px 64px;
     word-wrap: break-word;
   }}
   .markzap-content h1, .markzap-content h2 {{
-    border-bottom: 1px solid {border};
+    border-bottom: 1px solid var(--border);
     padding-bottom: .3em;
   }}
   .markzap-content h1, .markzap-content h2, .markzap-content h3,
@@ -66,7 +115,7 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
   }}
   .markzap-content h1 {{ font-size: 2em; }}
   .markzap-content h2 {{ font-size: 1.5em; }}
-  .markzap-content a {{ color: {link}; text-decoration: none; }}
+  .markzap-content a {{ color: var(--link); text-decoration: none; }}
   .markzap-content a:hover {{ text-decoration: underline; }}
   .markzap-content p, .markzap-content ul, .markzap-content ol {{ margin: 0 0 1em; }}
   .markzap-content li {{ margin: .25em 0; }}
@@ -74,14 +123,14 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
   .markzap-content code {{
     font-family: ui-monospace, SFMono-Regular, "SF Mono", Menlo, Consolas, monospace;
     font-size: .88em;
-    background: {code_bg};
-    color: {code_fg};
+    background: var(--code-bg);
+    color: var(--code-fg);
     padding: .2em .4em;
     border-radius: 4px;
   }}
   .markzap-content pre {{
-    background: {code_bg};
-    border: 1px solid {border};
+    background: var(--code-bg);
+    border: 1px solid var(--border);
     border-radius: 6px;
     padding: 14px 16px;
     overflow: auto;
@@ -94,8 +143,8 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
   .markzap-content blockquote {{
     margin: 0 0 1em;
     padding: 0 1em;
-    color: {muted};
-    border-left: .25em solid {quote_border};
+    color: var(--muted);
+    border-left: .25em solid var(--quote-border);
   }}
   .markzap-content table {{
     border-collapse: collapse;
@@ -104,13 +153,13 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
     overflow: auto;
   }}
   .markzap-content th, .markzap-content td {{
-    border: 1px solid {border};
+    border: 1px solid var(--border);
     padding: 6px 13px;
   }}
-  .markzap-content th {{ background: {code_bg}; font-weight: 600; }}
+  .markzap-content th {{ background: var(--code-bg); font-weight: 600; }}
   .markzap-content hr {{
     border: 0;
-    border-top: 1px solid {border};
+    border-top: 1px solid var(--border);
     margin: 2em 0;
   }}
   .markzap-content ul.contains-task-list {{ list-style: none; padding-left: 1em; }}
@@ -121,17 +170,17 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
     display: none;
     align-items: center;
     gap: 6px;
-    background: {bg};
-    border: 1px solid {border};
+    background: var(--bg);
+    border: 1px solid var(--border);
     border-radius: 6px;
     padding: 6px 8px;
     box-shadow: 0 2px 10px rgba(0,0,0,.25);
     z-index: 9999;
   }}
   #markzap-find input {{
-    background: {code_bg};
-    color: {fg};
-    border: 1px solid {border};
+    background: var(--code-bg);
+    color: var(--fg);
+    border: 1px solid var(--border);
     border-radius: 4px;
     padding: 4px 8px;
     font-size: 13px;
@@ -140,21 +189,40 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
   }}
   #markzap-find button {{
     background: transparent;
-    color: {muted};
+    color: var(--muted);
     border: none;
     cursor: pointer;
     font-size: 14px;
     padding: 2px 6px;
   }}
-  #markzap-find button:hover {{ color: {fg}; }}
+  #markzap-find button:hover {{ color: var(--fg); }}
+  #markzap-find button.toggle {{ font-size: 12px; border-radius: 4px; }}
+  #markzap-find button.toggle[aria-pressed="true"] {{
+    color: var(--fg);
+    background: var(--code-bg);
+  }}
+  #markzap-find-count {{
+    color: var(--muted);
+    font-size: 12px;
+    min-width: 64px;
+    text-align: center;
+  }}
+  mark.markzap-match {{ background: rgba(255, 200, 0, .35); color: inherit; border-radius: 2px; }}
+  mark.markzap-match.current {{ background: rgba(255, 140, 0, .75); }}
+</style>
+<style id="markzap-custom">
+{custom_css}
 </style>
 </head>
 <body>
 <div id="markzap-find">
-  <input type="text" id="markzap-find-input" placeholder="Rechercher…" autocomplete="off">
-  <button id="markzap-find-prev" title="Précédent">↑</button>
-  <button id="markzap-find-next" title="Suivant">↓</button>
-  <button id="markzap-find-close" title="Fermer">✕</button>
+  <input type="text" id="markzap-find-input" placeholder="{find_placeholder}" autocomplete="off">
+  <button id="markzap-find-case" class="toggle" title="{find_case_sensitive}" aria-pressed="false">Aa</button>
+  <button id="markzap-find-word" class="toggle" title="{find_whole_word}" aria-pressed="false">W</button>
+  <span id="markzap-find-count"></span>
+  <button id="markzap-find-prev" title="{find_previous}">↑</button>
+  <button id="markzap-find-next" title="{find_next}">↓</button>
+  <button id="markzap-find-close" title="{find_close}">✕</button>
 </div>
 <div class="markzap-content">
 {body}
@@ -163,6 +231,90 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
 (function() {{
   var bar = document.getElementById('markzap-find');
   var input = document.getElementById('markzap-find-input');
+  var count = document.getElementById('markzap-find-count');
+  var caseButton = document.getElementById('markzap-find-case');
+  var wordButton = document.getElementById('markzap-find-word');
+  var content = document.querySelector('.markzap-content');
+  var marks = [];
+  var current = -1;
+
+  function clearMarks() {{
+    marks.forEach(function(mark) {{
+      var parent = mark.parentNode;
+      parent.replaceChild(document.createTextNode(mark.textContent), mark);
+      parent.normalize();
+    }});
+    marks = [];
+    current = -1;
+  }}
+  function pattern() {{
+    var q = input.value.replace(/[.*+?^${{}}()|[\]\\]/g, '\\$&');
+    // `\b` only knows ASCII letters, so look for word characters in any
+    // script around the match instead.
+    if (wordButton.getAttribute('aria-pressed') === 'true') {{
+      q = '(?<![\\p{{L}}\\p{{N}}_])' + q + '(?![\\p{{L}}\\p{{N}}_])';
+    }}
+    var flags = caseButton.getAttribute('aria-pressed') === 'true' ? 'gu' : 'giu';
+    return new RegExp(q, flags);
+  }}
+  // Wrap every match in a <mark>. Matches are found within single text
+  // nodes, so text split across inline elements is not matched.
+  function search() {{
+    clearMarks();
+    if (input.value) {{
+      var re = pattern();
+      var walker = document.createTreeWalker(content, NodeFilter.SHOW_TEXT, {{
+        acceptNode: function(node) {{
+          var tag = node.parentNode.nodeName;
+          return tag === 'SCRIPT' || tag === 'STYLE' ? NodeFilter.FILTER_REJECT : NodeFilter.FILTER_ACCEPT;
+        }}
+      }});
+      var nodes = [];
+      while (walker.nextNode()) nodes.push(walker.currentNode);
+      nodes.forEach(function(node) {{
+        var text = node.nodeValue;
+        var found = [];
+        var m;
+        re.lastIndex = 0;
+        while ((m = re.exec(text)) !== null) {{
+          if (m[0].length === 0) {{ re.lastIndex++; continue; }}
+          found.push([m.index, m.index + m[0].length]);
+        }}
+        for (var i = found.length - 1; i >= 0; i--) {{
+          var match = node.splitText(found[i][0]);
+          match.splitText(found[i][1] - found[i][0]);
+          var mark = document.createElement('mark');
+          mark.className = 'markzap-match';
+          match.parentNode.replaceChild(mark, match);
+          mark.appendChild(match);
+        }}
+      }});
+      marks = Array.prototype.slice.call(content.querySelectorAll('mark.markzap-match'));
+    }}
+    select(marks.length ? 0 : -1);
+  }}
+  function select(index) {{
+    if (current >= 0 && marks[current]) marks[current].classList.remove('current');
+    current = index;
+    if (current >= 0) {{
+      marks[current].classList.add('current');
+      marks[current].scrollIntoView({{ block: 'center' }});
+      count.textContent = (current + 1) + ' ' + {find_of} + ' ' + marks.length;
+    }} else {{
+      count.textContent = input.value ? {find_no_results} : '';
+    }}
+  }}
+  function find(backwards) {{
+    if (!marks.length) return;
+    var n = marks.length;
+    select(backwards ? (current + n - 1) % n : (current + 1) % n);
+  }}
+  function toggle(button) {{
+    var pressed = button.getAttribute('aria-pressed') === 'true';
+    button.setAttribute('aria-pressed', pressed ? 'false' : 'true');
+    search();
+    input.focus();
+  }}
   function openBar() {{
     bar.style.display = 'flex';
     input.focus();
@@ -170,13 +322,14 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
   }}
   function closeBar() {{
     bar.style.display = 'none';
-    window.getSelection().removeAllRanges();
-  }}
-  function find(backwards) {{
-    var q = input.value;
-    if (!q) return;
-    window.find(q, false, backwards, true, false, false, false);
+    clearMarks();
+    count.textContent = '';
   }}
+  window.markzapFind = {{ open: openBar, close: closeBar }};
+
+  input.addEventListener('input', search);
+  caseButton.addEventListener('click', function() {{ toggle(caseButton); }});
+  wordButton.addEventListener('click', function() {{ toggle(wordButton); }});
   document.addEventListener('keydown', function(e) {{
     if ((e.metaKey || e.ctrlKey) && e.key.toLowerCase() === 'f') {{
       e.preventDefault();
@@ -197,19 +350,116 @@ pub fn render_markdown_page(markdown: &str, dark: bool) -> String {
   document.getElementById('markzap-find-next').addEventListener('click', function() {{ find(false); }});
   document.getElementById('markzap-find-prev').addEventListener('click', function() {{ find(true); }});
   document.getElementById('markzap-find-close').addEventListener('click', closeBar);
+
+  var scrollTimer = null;
+  window.addEventListener('scroll', function() {{
+    clearTimeout(scrollTimer);
+    scrollTimer = setTimeout(function() {{
+      if (window.ipc) {{
+        window.ipc.postMessage(JSON.stringify({{ type: 'scroll', y: window.scrollY }}));
+      }}
+    }}, 150);
+  }});
+  if ({editable_tasks}) {{
+    content.querySelectorAll('li[data-sourcepos] input[type=checkbox]').forEach(function(box) {{
+      box.disabled = false;
+      box.addEventListener('change', function() {{
+        var line = parseInt(box.closest('li[data-sourcepos]').dataset.sourcepos, 10);
+        if (window.ipc && line) {{
+          window.ipc.postMessage(JSON.stringify({{ type: 'task', line: line }}));
+        }}
+      }});
+    }});
+  }}
+  window.addEventListener('load', function() {{ window.scrollTo(0, {scroll_y}); }});
 }})();
 </script>
 </body>
 </html>"#,
-        scheme = if dark { "dark" } else { "light" },
-        bg = bg,
-        fg = fg,
-        muted = muted,
-        border = border,
-        code_bg = code_bg,
-        code_fg = code_fg,
-        quote_border = quote_border,
-        link = link,
+        palette_css = palette_css,
+        find_placeholder = escape_attribute(page.strings.find_placeholder),
+        find_previous = escape_attribute(page.strings.find_previous),
+        find_next = escape_attribute(page.strings.find_next),
+        find_close = escape_attribute(page.strings.find_close),
+        find_case_sensitive = escape_attribute(page.strings.find_case_sensitive),
+        find_whole_word = escape_attribute(page.strings.find_whole_word),
+        find_of = js_string(page.strings.find_of),
+        find_no_results = js_string(page.strings.find_no_results),
         body = body,
+        custom_css = custom_css,
+        scroll_y = page.scroll_y,
+        editable_tasks = page.editable_tasks,
+        content_width = page.content_width,
+        font_size = font_size(page.zoom),
+    )
+}
+
+/// `text` escaped for a double-quoted HTML attribute.
+fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `text` as a JavaScript string literal, safe inside a `<script>` element.
+fn js_string(text: &str) -> String {
+    serde_json::to_string(text)
+        .unwrap_or_default()
+        .replace("</", "<\\/")
+}
+
+/// CSS custom properties holding the theme colors, used by the page stylesheet.
+fn palette_css(page: &PageOptions) -> String {
+    let Palette {
+        bg,
+        fg,
+        muted,
+        border,
+        code_bg,
+        code_fg,
+        quote_border,
+        link,
+    } = page.palette;
+    format!(
+        ":root {{ color-scheme: {scheme}; --bg: {bg}; --fg: {fg}; --muted: {muted}; \
+         --border: {border}; --code-bg: {code_bg}; --code-fg: {code_fg}; \
+         --quote-border: {quote_border}; --link: {link}; }}",
+        scheme = if page.dark { "dark" } else { "light" },
+    )
+}
+
+/// Script opening the preview page's find bar, for the native Find action.
+pub const OPEN_FIND_SCRIPT: &str = "window.markzapFind && window.markzapFind.open();";
+
+fn font_size(zoom: f32) -> f32 {
+    16. * zoom
+}
+
+/// Script applying a new zoom factor to an already loaded page.
+pub fn zoom_script(zoom: f32) -> String {
+    format!("document.body.style.fontSize = '{}px';", font_size(zoom))
+}
+
+/// Script scrolling the preview to the `index`-th heading of the page.
+pub fn scroll_to_heading_script(index: usize) -> String {
+    format!(
+        "(function() {{\n\
+           var h = document.querySelectorAll('.markzap-content :is(h1, h2, h3, h4, h5, h6)')[{index}];\n\
+           if (h) h.scrollIntoView({{ block: 'start' }});\n\
+         }})();"
+    )
+}
+
+/// Script that re-themes an already loaded page in place, keeping its scroll
+/// position and find state (unlike reloading the HTML).
+pub fn retheme_script(page: &PageOptions) -> String {
+    // JSON string literals are valid JavaScript string literals.
+    let palette = serde_json::to_string(&palette_css(page)).unwrap_or_default();
+    let custom =
+        serde_json::to_string(page.custom_css.as_deref().unwrap_or("")).unwrap_or_default();
+    format!(
+        "document.getElementById('markzap-palette').textContent = {palette};\n\
+         document.getElementById('markzap-custom').textContent = {custom};"
     )
 }
@@ -0,0 +1,279 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+const MARKDOWN_EXTENSIONS: [&str; 3] = ["md", "markdown", "mdown"];
+const SNIPPET_CHARS: usize = 160;
+const MAX_RESULTS: usize = 200;
+
+struct IndexedFile {
+    path: PathBuf,
+    /// File name, lowercased for matching.
+    name: String,
+    text: String,
+    /// `text` lowercased line by line, for case-insensitive matching.
+    lines: Vec<String>,
+}
+
+/// In-memory index of the Markdown files under a folder.
+pub struct SearchIndex {
+    root: PathBuf,
+    files: Vec<IndexedFile>,
+}
+
+/// One matching file, with the line that best matches the query.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    /// Zero-based line of `snippet` in the file.
+    pub line: usize,
+    pub snippet: String,
+    pub score: usize,
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            MARKDOWN_EXTENSIONS
+                .iter()
+                .any(|md| ext.eq_ignore_ascii_case(md))
+        })
+}
+
+/// Every Markdown file under `root`, skipping hidden files and anything
+/// excluded by `.gitignore` and `.ignore` files.
+pub fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+        .filter(|path| is_markdown(path))
+        .collect()
+}
+
+impl SearchIndex {
+    /// Read every Markdown file under `root` (see `markdown_files`).
+    pub fn build(root: &Path) -> Self {
+        let mut files = Vec::new();
+        for path in markdown_files(root) {
+            let path = path.as_path();
+            let text = match fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(e) => {
+                    eprintln!("Skipping {:?} in search index: {}", path, e);
+                    continue;
+                }
+            };
+            files.push(IndexedFile {
+                path: path.to_path_buf(),
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_lowercase())
+                    .unwrap_or_default(),
+                lines: text.lines().map(str::to_lowercase).collect(),
+                text,
+            });
+        }
+        Self {
+            root: root.to_path_buf(),
+            files,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of files indexed.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Files containing every word of `query` (case-insensitive), best first.
+    ///
+    /// A file scores one point per occurrence of a query word, three per
+    /// occurrence in a heading and five per word found in its file name.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<SearchHit> = self
+            .files
+            .iter()
+            .filter_map(|file| file.search(&terms))
+            .collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        hits.truncate(MAX_RESULTS);
+        hits
+    }
+}
+
+impl IndexedFile {
+    fn search(&self, terms: &[String]) -> Option<SearchHit> {
+        let mut score = 0;
+        let mut found = vec![false; terms.len()];
+        // Line with the most distinct query words, the first one on ties.
+        let mut best: Option<(usize, usize)> = None;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let weight = if line.trim_start().starts_with('#') {
+                3
+            } else {
+                1
+            };
+            let mut distinct = 0;
+            for (term, found) in terms.iter().zip(found.iter_mut()) {
+                let count = line.matches(term.as_str()).count();
+                if count > 0 {
+                    *found = true;
+                    distinct += 1;
+                    score += count * weight;
+                }
+            }
+            if distinct > 0 && best.is_none_or(|(_, most)| distinct > most) {
+                best = Some((index, distinct));
+            }
+        }
+        if !found.iter().all(|&found| found) {
+            return None;
+        }
+        score += 5 * terms
+            .iter()
+            .filter(|term| self.name.contains(term.as_str()))
+            .count();
+
+        let (line, _) = best?;
+        let text = self.text.lines().nth(line).unwrap_or_default();
+        Some(SearchHit {
+            path: self.path.clone(),
+            line,
+            snippet: snippet(text, &self.lines[line], terms),
+            score,
+        })
+    }
+}
+
+/// Up to `SNIPPET_CHARS` characters of `line` around the first occurrence of
+/// any of `terms` in `lowered` (the lowercased line).
+fn snippet(line: &str, lowered: &str, terms: &[String]) -> String {
+    let line = line.trim_end();
+    let chars = line.chars().count();
+    if chars <= SNIPPET_CHARS {
+        return line.trim_start().to_string();
+    }
+    // Lowercasing may change byte lengths, so locate the match in characters.
+    let match_char = terms
+        .iter()
+        .filter_map(|term| lowered.find(term.as_str()))
+        .min()
+        .map(|byte| lowered[..byte].chars().count())
+        .unwrap_or(0);
+    let start = match_char
+        .saturating_sub(SNIPPET_CHARS / 3)
+        .min(chars - SNIPPET_CHARS);
+    let mut snippet: String = line.chars().skip(start).take(SNIPPET_CHARS).collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if start + SNIPPET_CHARS < chars {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, text: &str) -> IndexedFile {
+        IndexedFile {
+            path: PathBuf::from(name),
+            name: name.to_lowercase(),
+            lines: text.lines().map(str::to_lowercase).collect(),
+            text: text.to_string(),
+        }
+    }
+
+    fn terms(query: &str) -> Vec<String> {
+        query.split_whitespace().map(str::to_lowercase).collect()
+    }
+
+    #[test]
+    fn short_line_is_kept_whole() {
+        assert_eq!(
+            snippet("  Hello world  ", "  hello world  ", &terms("world")),
+            "Hello world"
+        );
+    }
+
+    #[test]
+    fn long_line_is_cut_around_the_match() {
+        let line = format!("{}needle{}", "a".repeat(300), "b".repeat(300));
+        let snippet = snippet(&line, &line, &terms("needle"));
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 2);
+    }
+
+    #[test]
+    fn match_near_the_edges() {
+        let line = format!("needle{}", "b".repeat(300));
+        let start = snippet(&line, &line, &terms("needle"));
+        assert!(start.starts_with("needle") && start.ends_with('…'));
+
+        let line = format!("{}needle", "a".repeat(300));
+        let end = snippet(&line, &line, &terms("needle"));
+        assert!(end.starts_with('…') && end.ends_with("needle"));
+    }
+
+    #[test]
+    fn match_after_multibyte_characters() {
+        let line = format!("{}Needle{}", "é".repeat(300), "b".repeat(300));
+        let lowered = line.to_lowercase();
+        assert!(snippet(&line, &lowered, &terms("needle")).contains("Needle"));
+    }
+
+    #[test]
+    fn long_line_is_cut_around_any_term() {
+        // The line matches on its second term only.
+        let line = format!("{}needle{}", "a".repeat(300), "b".repeat(300));
+        let second = snippet(&line, &line, &terms("haystack needle"));
+        assert!(second.starts_with('…') && second.contains("needle"));
+
+        // The earliest of the terms found is kept.
+        let line = format!("{}first{}second", "a".repeat(300), "b".repeat(300));
+        let earliest = snippet(&line, &line, &terms("second first"));
+        assert!(earliest.contains("first") && !earliest.contains("second"));
+    }
+
+    #[test]
+    fn file_must_contain_every_term() {
+        let file = file("notes.md", "alpha\nbeta");
+        assert!(file.search(&terms("alpha beta")).is_some());
+        assert!(file.search(&terms("alpha gamma")).is_none());
+    }
+
+    #[test]
+    fn headings_and_file_names_score_higher() {
+        let hit = file("notes.md", "# Rust\nrust")
+            .search(&terms("rust"))
+            .unwrap();
+        assert_eq!(hit.score, 3 + 1);
+        let hit = file("rust.md", "rust").search(&terms("rust")).unwrap();
+        assert_eq!(hit.score, 1 + 5);
+    }
+
+    #[test]
+    fn best_line_has_the_most_terms() {
+        let hit = file("notes.md", "alpha\nalpha beta\nbeta alpha")
+            .search(&terms("alpha beta"))
+            .unwrap();
+        assert_eq!(hit.line, 1);
+        assert_eq!(hit.snippet, "alpha beta");
+    }
+}
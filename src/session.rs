@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gpui::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::documents::DocumentRegistry;
+use crate::paths;
+use crate::state::AppMode;
+
+const MAX_RECENT_FILES: usize = 10;
+const RECENT_FILES_FILE: &str = "recent.json";
+const SESSION_FILE: &str = "session.json";
+
+fn read_json<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(path) = paths::data_dir().map(|dir| dir.join(name)) else {
+        return T::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("Ignoring malformed {:?}: {}", path, e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+fn write_json<T: Serialize>(name: &str, value: &T) {
+    let Some(dir) = paths::data_dir() else {
+        return;
+    };
+    let path = dir.join(name);
+    let result = fs::create_dir_all(&dir)
+        .and_then(|_| serde_json::to_string_pretty(value).map_err(Into::into))
+        .and_then(|json| fs::write(&path, json));
+    if let Err(e) = result {
+        eprintln!("Error saving {:?}: {}", path, e);
+    }
+}
+
+/// Most recently opened files, newest first. Shown in the File menu, which
+/// `main` rebuilds whenever this global changes.
+#[derive(Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl Global for RecentFiles {}
+
+impl RecentFiles {
+    pub fn load() -> Self {
+        read_json(RECENT_FILES_FILE)
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Move `path` to the top of the list and persist it.
+    pub fn add(path: &Path, cx: &mut App) {
+        let recent = cx.default_global::<Self>();
+        recent.paths.retain(|p| p != path);
+        recent.paths.insert(0, path.to_path_buf());
+        recent.paths.truncate(MAX_RECENT_FILES);
+        write_json(RECENT_FILES_FILE, recent);
+    }
+
+    pub fn clear(cx: &mut App) {
+        let recent = cx.default_global::<Self>();
+        recent.paths.clear();
+        write_json(RECENT_FILES_FILE, recent);
+    }
+}
+
+/// Window placement, mirroring `WindowBounds` (which is not serializable).
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum SavedBounds {
+    Windowed(Bounds<Pixels>),
+    Maximized(Bounds<Pixels>),
+    Fullscreen(Bounds<Pixels>),
+}
+
+impl From<WindowBounds> for SavedBounds {
+    fn from(bounds: WindowBounds) -> Self {
+        match bounds {
+            WindowBounds::Windowed(b) => Self::Windowed(b),
+            WindowBounds::Maximized(b) => Self::Maximized(b),
+            WindowBounds::Fullscreen(b) => Self::Fullscreen(b),
+        }
+    }
+}
+
+impl From<SavedBounds> for WindowBounds {
+    fn from(bounds: SavedBounds) -> Self {
+        match bounds {
+            SavedBounds::Windowed(b) => Self::Windowed(b),
+            SavedBounds::Maximized(b) => Self::Maximized(b),
+            SavedBounds::Fullscreen(b) => Self::Fullscreen(b),
+        }
+    }
+}
+
+/// State of one document window, enough to reopen it where it was.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionWindow {
+    pub path: PathBuf,
+    pub mode: AppMode,
+    pub scroll_y: f64,
+    pub bounds: Option<SavedBounds>,
+}
+
+/// The document windows open when MarkZap last quit.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Session {
+    pub windows: Vec<SessionWindow>,
+}
+
+impl Session {
+    pub fn load() -> Self {
+        read_json(SESSION_FILE)
+    }
+
+    /// Snapshot every open document window and persist it. Called when the
+    /// app quits, either through Quit or by closing the last window.
+    pub fn save(cx: &App) {
+        let session = Session {
+            windows: DocumentRegistry::all_views(cx)
+                .iter()
+                .filter_map(|view| view.read(cx).session_window())
+                .collect(),
+        };
+        write_json(SESSION_FILE, &session);
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use gpui::*;
+use serde::{Deserialize, Serialize};
+
+use crate::appearance::Appearance;
+use crate::format::FormatConfig;
+use crate::lint::LintConfig;
+use crate::paths;
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+/// User preferences, read from `settings.toml` in the platform config dir.
+///
+/// Missing keys fall back to their defaults, so a partial file is valid.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Delay after the last keystroke before edits are written to disk.
+    pub autosave_delay_ms: u64,
+    /// Width of newly opened document windows, in pixels.
+    pub window_width: f32,
+    /// Font family of the Markdown editor.
+    pub editor_font_family: String,
+    /// Maximum width of the rendered preview content, in pixels.
+    pub content_width: u32,
+    /// Preview theme: a built-in theme id or the name of a user `.css` theme.
+    pub preview_theme: String,
+    /// Light, dark, or follow the OS (`auto`).
+    pub appearance: Appearance,
+    /// UI language tag (`en`, `fr`), or `auto` to follow the OS locale.
+    pub language: String,
+    /// Reopen the windows of the last session when launched without a file.
+    pub restore_session: bool,
+    /// Open documents as tabs of an existing window instead of new windows.
+    pub open_in_tabs: bool,
+    /// Show the formatting toolbar above the editor.
+    pub format_toolbar: bool,
+    /// Also check `http(s)` links when checking links (slower, needs network).
+    pub check_external_links: bool,
+    /// Markdown lint rules.
+    pub lint: LintConfig,
+    /// Style of Format Document.
+    pub format: FormatConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            autosave_delay_ms: 2000,
+            window_width: 720.,
+            editor_font_family: "Menlo".to_string(),
+            content_width: 860,
+            preview_theme: "github".to_string(),
+            appearance: Appearance::Auto,
+            language: "auto".to_string(),
+            restore_session: false,
+            open_in_tabs: false,
+            format_toolbar: false,
+            check_external_links: false,
+            lint: LintConfig::default(),
+            format: FormatConfig::default(),
+        }
+    }
+}
+
+impl Global for Settings {}
+
+impl Settings {
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|dir| dir.join(SETTINGS_FILE))
+    }
+
+    /// Read the settings file, falling back to defaults if it is missing or invalid.
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|e| {
+            eprintln!("Ignoring invalid settings: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Read the settings file, with defaults if it is missing. Fails if the
+    /// file cannot be parsed.
+    pub fn try_load() -> anyhow::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| anyhow::anyhow!("{:?}: {}", path, e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The current settings. `main` installs them before opening any window.
+    pub fn get(cx: &App) -> &Self {
+        cx.global::<Self>()
+    }
+
+    /// Change the settings, persist them and notify observers.
+    pub fn update(cx: &mut App, f: impl FnOnce(&mut Self)) {
+        let mut settings = Self::get(cx).clone();
+        f(&mut settings);
+        if let Err(e) = settings.save() {
+            eprintln!("Error saving settings: {}", e);
+        }
+        cx.set_global(settings);
+    }
+
+    pub fn autosave_delay(&self) -> Duration {
+        Duration::from_millis(self.autosave_delay_ms)
+    }
+
+    /// Reload the settings whenever `settings.toml` changes on disk, whether
+    /// edited by hand or saved from the settings window. A file that does not
+    /// parse, e.g. half-saved, leaves the current settings in place.
+    pub fn watch(cx: &mut App) {
+        paths::watch_config_file(SETTINGS_FILE, cx, |cx| match Settings::try_load() {
+            Ok(settings) => {
+                if *Settings::get(cx) != settings {
+                    cx.set_global(settings);
+                }
+            }
+            Err(e) => eprintln!("Ignoring invalid settings: {}", e),
+        });
+    }
+}
@@ -24,9 +24,24 @@ pub fn detect_presentation(content: &str) -> bool {
     separator_count >= 3
 }
 
+/// URL of the Reveal.js theme stylesheet matching the app appearance.
+fn reveal_theme_url(dark: bool) -> String {
+    let theme = if dark { "black" } else { "white" };
+    format!("https://cdn.jsdelivr.net/npm/reveal.js@5/dist/theme/{theme}.css")
+}
+
+/// Script that swaps the Reveal.js theme of a running presentation in place.
+pub fn retheme_script(dark: bool) -> String {
+    format!(
+        "document.getElementById('markzap-reveal-theme').href = '{}';",
+        reveal_theme_url(dark)
+    )
+}
+
 /// Generates a self-contained HTML page that renders the markdown as a slide
 /// presentation using Reveal.js loaded from CDN.
-pub fn generate_presentation_html(markdown_content: &str) -> String {
+pub fn generate_presentation_html(markdown_content: &str, dark: bool) -> String {
+    let theme_url = reveal_theme_url(dark);
     // Escape markdown for safe embedding in a JS template literal
     let escaped = markdown_content
         .replace('\\', "\\\\")
@@ -42,7 +57,7 @@ pub fn generate_presentation_html(markdown_content: &str) -> String {
     <meta name="viewport" content="width=device-width, initial-scale=1">
     <title>MarkZap Presentation</title>
     <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/reveal.js@5/dist/reveal.css">
-    <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/reveal.js@5/dist/theme/white.css">
+    <link rel="stylesheet" id="markzap-reveal-theme" href="{theme_url}">
     <style>
         body {{ margin: 0; padding: 0; overflow: hidden; }}
         .reveal {{ height: 100vh; }}
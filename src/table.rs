@@ -0,0 +1,577 @@
+use std::cmp::Ordering;
+
+use crate::editing::{self, Cursor, Edit};
+
+/// Column alignment, from the delimiter row (`:--`, `:-:`, `--:`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// A GFM pipe table as cell text: the header row, then the body rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub alignments: Vec<Alignment>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Cells of a table row, trimmed. Pipes escaped as `\|` or inside code
+/// spans do not split cells; the optional leading and trailing pipes are
+/// dropped.
+pub fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars();
+    let mut in_code = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                if let Some(next) = chars.next() {
+                    cell.push(next);
+                }
+            }
+            '`' => {
+                in_code = !in_code;
+                cell.push(c);
+            }
+            '|' if !in_code => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    // A trailing pipe ends the last cell rather than starting an empty one.
+    if !cell.trim().is_empty() || !line.trim_end().ends_with('|') {
+        cells.push(cell);
+    }
+    cells
+        .into_iter()
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+/// The alignments of a delimiter row, or `None` if `line` is not one.
+fn parse_delimiter_row(line: &str) -> Option<Vec<Alignment>> {
+    let cells = split_row(line);
+    if cells.is_empty() {
+        return None;
+    }
+    cells
+        .iter()
+        .map(|cell| {
+            let left = cell.starts_with(':');
+            let right = cell.ends_with(':');
+            let dashes = cell.trim_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (left, right) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            })
+        })
+        .collect()
+}
+
+/// Whether `line` could be a row of a pipe table.
+pub fn is_row(line: &str) -> bool {
+    line.contains('|')
+}
+
+impl Table {
+    /// Parse the lines of a pipe table: header, delimiter row, body rows.
+    pub fn parse(lines: &[&str]) -> Option<Self> {
+        let (header, rest) = lines.split_first()?;
+        let (delimiter, body) = rest.split_first()?;
+        let mut alignments = parse_delimiter_row(delimiter)?;
+        let mut rows: Vec<Vec<String>> = std::iter::once(header)
+            .chain(body)
+            .map(|line| split_row(line))
+            .collect();
+        let columns = rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(alignments.len());
+        alignments.resize(columns, Alignment::None);
+        for row in &mut rows {
+            row.resize(columns, String::new());
+        }
+        Some(Self { alignments, rows })
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.alignments.len()
+    }
+
+    /// Width of each column once aligned, at least the three dashes of the
+    /// delimiter row.
+    fn widths(&self) -> Vec<usize> {
+        (0..self.column_count())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .map(|row| width(&row[column]))
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect()
+    }
+
+    /// The table as lines, every column padded to its widest cell.
+    pub fn to_lines(&self) -> Vec<String> {
+        let widths = self.widths();
+
+        let format_row = |row: &[String]| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .zip(&self.alignments)
+                .map(|((cell, &w), alignment)| {
+                    let padding = w - width(cell);
+                    match alignment {
+                        Alignment::Right => format!("{}{}", " ".repeat(padding), cell),
+                        Alignment::Center => format!(
+                            "{}{}{}",
+                            " ".repeat(padding / 2),
+                            cell,
+                            " ".repeat(padding - padding / 2)
+                        ),
+                        Alignment::None | Alignment::Left => {
+                            format!("{}{}", cell, " ".repeat(padding))
+                        }
+                    }
+                })
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+
+        let delimiters: Vec<String> = widths
+            .iter()
+            .zip(&self.alignments)
+            .map(|(&w, alignment)| match alignment {
+                Alignment::None => "-".repeat(w),
+                Alignment::Left => format!(":{}", "-".repeat(w - 1)),
+                Alignment::Right => format!("{}:", "-".repeat(w - 1)),
+                Alignment::Center => format!(":{}:", "-".repeat(w - 2)),
+            })
+            .collect();
+
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        let mut rows = self.rows.iter();
+        if let Some(header) = rows.next() {
+            lines.push(format_row(header));
+        }
+        lines.push(format!("| {} |", delimiters.join(" | ")));
+        lines.extend(rows.map(|row| format_row(row)));
+        lines
+    }
+}
+
+/// Display width of a cell, in characters.
+fn width(cell: &str) -> usize {
+    cell.chars().count()
+}
+
+/// Realign the table of `lines`, or `None` if they are not a table.
+pub fn align(lines: &[&str]) -> Option<Vec<String>> {
+    Table::parse(lines).map(|table| table.to_lines())
+}
+
+/// The table around the cursor, and the cell the cursor is in.
+struct TableAt {
+    /// Lines of the table in the document, `start..end`.
+    start: usize,
+    end: usize,
+    table: Table,
+    /// Index in `table.rows` (0 is the header, the delimiter row counts as
+    /// the header).
+    row: usize,
+    column: usize,
+}
+
+/// Number of cell separators in `text`, skipping escaped pipes and pipes
+/// inside code spans.
+fn count_pipes(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_code = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '`' => in_code = !in_code,
+            '|' if !in_code => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+fn table_at(lines: &[String], cursor: Cursor) -> Option<TableAt> {
+    let current = lines.get(cursor.line)?;
+    if !is_row(current) || editing::in_code_block(lines, cursor.line) {
+        return None;
+    }
+    let mut start = cursor.line;
+    while start > 0 && is_row(&lines[start - 1]) {
+        start -= 1;
+    }
+    let mut end = cursor.line + 1;
+    while end < lines.len() && is_row(&lines[end]) {
+        end += 1;
+    }
+    // The header is the line above the first delimiter row; rows with pipes
+    // above it belong to something else.
+    let delimiter = (start + 1..end).find(|&ix| parse_delimiter_row(&lines[ix]).is_some())?;
+    let start = delimiter - 1;
+    if cursor.line < start {
+        return None;
+    }
+    let rows: Vec<&str> = lines[start..end].iter().map(String::as_str).collect();
+    let table = Table::parse(&rows)?;
+
+    let before: String = current.chars().take(cursor.column).collect();
+    let leading_pipe = current.trim_start().starts_with('|') as usize;
+    let column = count_pipes(&before)
+        .saturating_sub(leading_pipe)
+        .min(table.column_count() - 1);
+    let row = (cursor.line - start).saturating_sub(1);
+    Some(TableAt {
+        start,
+        end,
+        table,
+        row,
+        column,
+    })
+}
+
+impl TableAt {
+    /// Replace the table in `text` (split into `lines`) with its aligned
+    /// form, with the cursor at the end of the text of cell (`row`,
+    /// `column`).
+    fn into_edit(self, text: &str, mut lines: Vec<String>, row: usize, column: usize) -> Edit {
+        let widths = self.table.widths();
+        let cell = &self.table.rows[row][column];
+        let padding = widths[column] - width(cell);
+        let offset = match self.table.alignments[column] {
+            Alignment::Right => padding,
+            Alignment::Center => padding / 2,
+            Alignment::None | Alignment::Left => 0,
+        };
+        // "| " then, per previous column, its width and " | ".
+        let cell_start = 2 + widths[..column].iter().map(|w| w + 3).sum::<usize>();
+        let cursor = Cursor {
+            line: self.start + if row == 0 { 0 } else { row + 1 },
+            column: cell_start + offset + width(cell),
+        };
+        lines.splice(self.start..self.end, self.table.to_lines());
+        Edit::diff(text, &editing::join(&lines), cursor)
+    }
+}
+
+/// Run `f` on the table at the cursor; it returns the cell to put the
+/// cursor in, or `None` to leave the document unchanged.
+fn edit_table(
+    text: &str,
+    cursor: Cursor,
+    f: impl FnOnce(&mut Table, usize, usize) -> Option<(usize, usize)>,
+) -> Option<Edit> {
+    let lines = editing::lines(text);
+    let mut at = table_at(&lines, cursor)?;
+    let (row, column) = f(&mut at.table, at.row, at.column)?;
+    Some(at.into_edit(text, lines, row, column))
+}
+
+/// Tab in a table: align it and move to the next cell, adding a row after
+/// the last one.
+pub fn next_cell(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        if column + 1 < table.column_count() {
+            return Some((row, column + 1));
+        }
+        if row + 1 == table.rows.len() {
+            table.rows.push(vec![String::new(); table.column_count()]);
+        }
+        Some((row + 1, 0))
+    })
+}
+
+/// Shift-Tab in a table: align it and move to the previous cell.
+pub fn previous_cell(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        Some(match (row, column) {
+            (0, 0) => (0, 0),
+            (_, 0) => (row - 1, table.column_count() - 1),
+            _ => (row, column - 1),
+        })
+    })
+}
+
+/// Insert an empty row below the cursor's (below the header from the
+/// header or delimiter row).
+pub fn add_row(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        table
+            .rows
+            .insert(row + 1, vec![String::new(); table.column_count()]);
+        Some((row + 1, column))
+    })
+}
+
+/// Remove the body row at the cursor.
+pub fn remove_row(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        if row == 0 {
+            return None;
+        }
+        table.rows.remove(row);
+        Some((row.min(table.rows.len() - 1), column))
+    })
+}
+
+/// Insert an empty column after the cursor's.
+pub fn add_column(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        table.alignments.insert(column + 1, Alignment::None);
+        for cells in &mut table.rows {
+            cells.insert(column + 1, String::new());
+        }
+        Some((row, column + 1))
+    })
+}
+
+/// Remove the column at the cursor, unless it is the only one.
+pub fn remove_column(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        if table.column_count() == 1 {
+            return None;
+        }
+        table.alignments.remove(column);
+        for cells in &mut table.rows {
+            cells.remove(column);
+        }
+        Some((row, column.min(table.column_count() - 1)))
+    })
+}
+
+/// Numbers compare by value, other text case-insensitively.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Sort the body rows by the cursor's column: ascending, or descending if
+/// they already are ascending.
+pub fn sort(text: &str, cursor: Cursor) -> Option<Edit> {
+    edit_table(text, cursor, |table, row, column| {
+        let body = &mut table.rows[1..];
+        let ascending = body
+            .windows(2)
+            .all(|pair| compare_cells(&pair[0][column], &pair[1][column]) != Ordering::Greater);
+        body.sort_by(|a, b| {
+            let order = compare_cells(&a[column], &b[column]);
+            if ascending { order.reverse() } else { order }
+        });
+        Some((row, column))
+    })
+}
+
+/// Fields of a CSV or TSV line. Double quotes group fields containing the
+/// delimiter, `""` is a literal quote.
+fn split_delimited(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted || field.trim().is_empty() => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Whether a comma outside double quotes is followed by a space, as in
+/// prose rather than CSV.
+fn has_spaced_comma(line: &str) -> bool {
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted && chars.peek() == Some(&' ') => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// A Markdown table for pasted CSV or TSV text: at least two lines with
+/// the same number (two or more) of fields. CSV must also look like CSV
+/// rather than prose: no space after the commas, and a name for every
+/// column in the header. `None` for anything else.
+pub fn from_delimited(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.trim_end_matches(['\r', '\n']).lines().collect();
+    if lines.len() < 2 || lines.iter().all(|line| line.trim_start().starts_with('|')) {
+        return None;
+    }
+    let delimiter = ['\t', ',']
+        .into_iter()
+        .find(|&d| lines.iter().all(|line| line.contains(d)))?;
+    if delimiter == ',' && lines.iter().any(|line| has_spaced_comma(line)) {
+        return None;
+    }
+    let rows: Vec<Vec<String>> = lines
+        .iter()
+        .map(|line| {
+            split_delimited(line, delimiter)
+                .into_iter()
+                .map(|field| field.trim().replace('|', "\\|"))
+                .collect()
+        })
+        .collect();
+    let columns = rows[0].len();
+    if columns < 2 || rows.iter().any(|row| row.len() != columns) {
+        return None;
+    }
+    if delimiter == ',' && rows[0].iter().any(|name| name.is_empty()) {
+        return None;
+    }
+    let table = Table {
+        alignments: vec![Alignment::None; columns],
+        rows,
+    };
+    Some(table.to_lines().join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "Intro\n\n| a | b |\n|---|--:|\n| long cell | 2 |\n\nOutro";
+
+    fn at(line: usize, column: usize) -> Cursor {
+        Cursor { line, column }
+    }
+
+    #[test]
+    fn split_row_cells() {
+        assert_eq!(split_row("| a | b |"), vec!["a", "b"]);
+        assert_eq!(split_row("a | b"), vec!["a", "b"]);
+        assert_eq!(split_row("| a | |"), vec!["a", ""]);
+        assert_eq!(split_row(r"| a \| b | c |"), vec![r"a \| b", "c"]);
+        assert_eq!(split_row("| `a | b` | c |"), vec!["`a | b`", "c"]);
+    }
+
+    #[test]
+    fn align_pads_columns() {
+        let aligned = align(&["|a|b|", "|:-:|-|", "|ccccc|d|"]).unwrap();
+        assert_eq!(
+            aligned,
+            vec!["|   a   | b   |", "| :---: | --- |", "| ccccc | d   |"]
+        );
+        assert_eq!(align(&["a | b", "not a delimiter"]), None);
+    }
+
+    #[test]
+    fn next_cell_aligns_and_moves() {
+        let edit = next_cell(TABLE, at(2, 3)).unwrap();
+        assert_eq!(
+            edit.apply(TABLE),
+            "Intro\n\n| a         |   b |\n| --------- | --: |\n| long cell |   2 |\n\nOutro"
+        );
+        assert_eq!(edit.cursor, at(2, 17));
+    }
+
+    #[test]
+    fn next_cell_adds_a_row_after_the_last() {
+        let edit = next_cell(TABLE, at(4, 14)).unwrap();
+        let text = edit.apply(TABLE);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[5], "|           |     |");
+        assert_eq!(edit.cursor, at(5, 2));
+    }
+
+    #[test]
+    fn previous_cell_moves_back() {
+        // From the first cell of the body to the last cell of the header.
+        let edit = previous_cell(TABLE, at(4, 3)).unwrap();
+        let text = edit.apply(TABLE);
+        assert_eq!(text.lines().nth(2), Some("| a         |   b |"));
+        assert_eq!(edit.cursor, at(2, 17));
+        // The first cell stays put.
+        assert_eq!(previous_cell(TABLE, at(2, 2)).unwrap().cursor, at(2, 3));
+    }
+
+    #[test]
+    fn cells_outside_tables() {
+        assert_eq!(next_cell(TABLE, at(0, 2)), None);
+        assert_eq!(next_cell("a | b\nc | d", at(0, 1)), None);
+        assert_eq!(next_cell("```\n| a |\n|---|\n```", at(1, 2)), None);
+    }
+
+    #[test]
+    fn sort_by_column() {
+        let text = "| n | name |\n|---|---|\n| 10 | b |\n| 9 | A |\n| 100 | c |";
+        let ascending = sort(text, at(2, 2)).unwrap().apply(text);
+        let column: Vec<String> = ascending
+            .lines()
+            .skip(2)
+            .map(|line| split_row(line)[0].clone())
+            .collect();
+        assert_eq!(column, vec!["9", "10", "100"]);
+
+        // Sorting sorted rows again reverses them.
+        let descending = sort(&ascending, at(2, 2)).unwrap().apply(&ascending);
+        let names: Vec<String> = descending
+            .lines()
+            .skip(2)
+            .map(|line| split_row(line)[1].clone())
+            .collect();
+        assert_eq!(names, vec!["c", "b", "A"]);
+    }
+
+    #[test]
+    fn csv_and_tsv() {
+        assert_eq!(
+            from_delimited("name,age\nAda,36\n").unwrap(),
+            "| name | age |\n| ---- | --- |\n| Ada  | 36  |"
+        );
+        assert_eq!(
+            from_delimited("name\tnote\nAda\ta, b | c\n").unwrap(),
+            "| name | note      |\n| ---- | --------- |\n| Ada  | a, b \\| c |"
+        );
+        assert_eq!(
+            from_delimited("name,quote\nAda,\"Hello, \"\"world\"\"\"").unwrap(),
+            "| name | quote          |\n| ---- | -------------- |\n| Ada  | Hello, \"world\" |"
+        );
+    }
+
+    #[test]
+    fn prose_is_not_csv() {
+        assert_eq!(from_delimited("Hello, world\nSee you, Bob"), None);
+        assert_eq!(from_delimited("a,b\nc,d,e"), None);
+        assert_eq!(from_delimited(",b\nc,d"), None);
+        assert_eq!(from_delimited("one line,only"), None);
+        assert_eq!(from_delimited("| a | b |\n|---|---|"), None);
+    }
+}
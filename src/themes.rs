@@ -0,0 +1,168 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths;
+
+/// Colors used by the preview stylesheet.
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub bg: &'static str,
+    pub fg: &'static str,
+    pub muted: &'static str,
+    pub border: &'static str,
+    pub code_bg: &'static str,
+    pub code_fg: &'static str,
+    pub quote_border: &'static str,
+    pub link: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinTheme {
+    GitHub,
+    Solarized,
+    Sepia,
+    HighContrast,
+}
+
+impl BuiltinTheme {
+    pub const ALL: [Self; 4] = [
+        Self::GitHub,
+        Self::Solarized,
+        Self::Sepia,
+        Self::HighContrast,
+    ];
+
+    /// Identifier stored in the settings file.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::Solarized => "solarized",
+            Self::Sepia => "sepia",
+            Self::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::GitHub => "GitHub",
+            Self::Solarized => "Solarized",
+            Self::Sepia => "Sepia",
+            Self::HighContrast => "High Contrast",
+        }
+    }
+
+    pub fn palette(self, dark: bool) -> Palette {
+        let (bg, fg, muted, border, code_bg, code_fg, quote_border, link) = match (self, dark) {
+            (Self::GitHub, true) => (
+                "#1e1e1e", "#e6e6e6", "#9a9a9a", "#3a3a3a", "#2a2a2a", "#e6e6e6", "#4a4a4a",
+                "#5aa9e6",
+            ),
+            (Self::GitHub, false) => (
+                "#ffffff", "#1f2328", "#656d76", "#d0d7de", "#f4f4f4", "#1f2328", "#d0d7de",
+                "#0969da",
+            ),
+            (Self::Solarized, true) => (
+                "#002b36", "#839496", "#586e75", "#073642", "#073642", "#93a1a1", "#586e75",
+                "#268bd2",
+            ),
+            (Self::Solarized, false) => (
+                "#fdf6e3", "#657b83", "#93a1a1", "#eee8d5", "#eee8d5", "#586e75", "#93a1a1",
+                "#268bd2",
+            ),
+            (Self::Sepia, true) => (
+                "#2b2118", "#e6d8c0", "#a89479", "#4a3b2c", "#3a2d20", "#e6d8c0", "#5c4a38",
+                "#d9a066",
+            ),
+            (Self::Sepia, false) => (
+                "#f4ecd8", "#5b4636", "#8a7560", "#e0d3b8", "#ebe0c8", "#5b4636", "#c9b594",
+                "#8b4513",
+            ),
+            (Self::HighContrast, true) => (
+                "#000000", "#ffffff", "#dddddd", "#ffffff", "#1a1a1a", "#ffffff", "#ffffff",
+                "#ffff00",
+            ),
+            (Self::HighContrast, false) => (
+                "#ffffff", "#000000", "#333333", "#000000", "#f0f0f0", "#000000", "#000000",
+                "#0000ee",
+            ),
+        };
+        Palette {
+            bg,
+            fg,
+            muted,
+            border,
+            code_bg,
+            code_fg,
+            quote_border,
+            link,
+        }
+    }
+}
+
+/// A preview theme: either built in, or a user `.css` file in the themes dir.
+///
+/// User themes are layered over the GitHub palette, so they only need to
+/// override what they change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewTheme {
+    Builtin(BuiltinTheme),
+    User(String),
+}
+
+impl PreviewTheme {
+    /// Resolve the theme name stored in the settings. Unknown names fall back
+    /// to GitHub when rendering, since their CSS file cannot be read.
+    pub fn from_id(id: &str) -> Self {
+        BuiltinTheme::ALL
+            .into_iter()
+            .find(|theme| theme.id() == id)
+            .map(Self::Builtin)
+            .unwrap_or_else(|| Self::User(id.to_string()))
+    }
+
+    pub fn palette(&self, dark: bool) -> Palette {
+        match self {
+            Self::Builtin(theme) => theme.palette(dark),
+            Self::User(_) => BuiltinTheme::GitHub.palette(dark),
+        }
+    }
+
+    /// The user stylesheet of this theme, if any.
+    pub fn custom_css(&self) -> Option<String> {
+        let Self::User(name) = self else {
+            return None;
+        };
+        let path = themes_dir()?.join(format!("{}.css", name));
+        fs::read_to_string(&path)
+            .inspect_err(|e| eprintln!("Error reading theme {:?}: {}", path, e))
+            .ok()
+    }
+}
+
+/// Directory scanned for user `.css` themes.
+pub fn themes_dir() -> Option<PathBuf> {
+    paths::config_dir().map(|dir| dir.join("themes"))
+}
+
+/// Every selectable theme as `(id, label)`: built-in ones first, then user
+/// themes sorted by name.
+pub fn available_themes() -> Vec<(String, String)> {
+    let mut themes: Vec<(String, String)> = BuiltinTheme::ALL
+        .into_iter()
+        .map(|theme| (theme.id().to_string(), theme.label().to_string()))
+        .collect();
+
+    let mut user_themes: Vec<String> = themes_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "css"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .filter(|name| !themes.iter().any(|(id, _)| id == name))
+        .collect();
+    user_themes.sort();
+    themes.extend(user_themes.into_iter().map(|name| (name.clone(), name)));
+    themes
+}
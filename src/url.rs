@@ -0,0 +1,350 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::open_requests::OpenRequest;
+
+/// Scheme of MarkZap deep links, e.g. `markzap://open?path=/notes/a.md&line=42`.
+pub const SCHEME: &str = "markzap";
+
+/// Why a URL cannot be opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlError {
+    /// Neither `file:` nor `markzap:`.
+    UnsupportedScheme(String),
+    /// A `file:` URL naming another machine.
+    RemoteHost(String),
+    /// A `markzap:` URL with an action other than `open`.
+    UnsupportedAction(String),
+    /// A `markzap://open` URL without a `path` parameter.
+    MissingPath,
+    /// A `line` parameter that is not a positive integer.
+    InvalidLine(String),
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedScheme(scheme) => write!(f, "unsupported URL scheme {:?}", scheme),
+            Self::RemoteHost(host) => write!(f, "file on remote host {:?}", host),
+            Self::UnsupportedAction(action) => write!(f, "unsupported action {:?}", action),
+            Self::MissingPath => write!(f, "missing path parameter"),
+            Self::InvalidLine(line) => write!(f, "invalid line number {:?}", line),
+        }
+    }
+}
+
+impl std::error::Error for UrlError {}
+
+/// Whether `arg` looks like a URL (`scheme:...`) rather than a path.
+///
+/// A single-letter scheme is a Windows drive (`C:\notes.md`), not a URL.
+pub fn is_url(arg: &str) -> bool {
+    match arg.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.len() > 1
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// Parse a `file:` URL or a `markzap://open` deep link into an open request.
+pub fn parse_open_url(url: &str) -> Result<OpenRequest, UrlError> {
+    let (scheme, rest) = url
+        .split_once(':')
+        .ok_or_else(|| UrlError::UnsupportedScheme(String::new()))?;
+    if scheme.eq_ignore_ascii_case("file") {
+        file_url_to_path(rest).map(OpenRequest::from)
+    } else if scheme.eq_ignore_ascii_case(SCHEME) {
+        parse_deep_link(rest)
+    } else {
+        Err(UrlError::UnsupportedScheme(scheme.to_string()))
+    }
+}
+
+/// Convert the part of a `file:` URL after the scheme to a local path.
+///
+/// Accepts `///path`, `//localhost/path` and `/path`; the query and fragment
+/// are dropped and the path is percent-decoded, `+` included literally.
+fn file_url_to_path(rest: &str) -> Result<PathBuf, UrlError> {
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let path = match rest.strip_prefix("//") {
+        Some(authority_and_path) => {
+            let (host, path) = match authority_and_path.find('/') {
+                Some(slash) => authority_and_path.split_at(slash),
+                None => (authority_and_path, "/"),
+            };
+            if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+                return Err(UrlError::RemoteHost(host.to_string()));
+            }
+            path
+        }
+        None => rest,
+    };
+    Ok(bytes_to_path(percent_decode(path, false), true))
+}
+
+/// Parse the part of a `markzap:` URL after the scheme: `//open?query`.
+fn parse_deep_link(rest: &str) -> Result<OpenRequest, UrlError> {
+    let rest = rest.strip_prefix("//").unwrap_or(rest);
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let action = action.trim_end_matches('/');
+    if !action.eq_ignore_ascii_case("open") {
+        return Err(UrlError::UnsupportedAction(action.to_string()));
+    }
+
+    let mut path = None;
+    let mut line = None;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "path" => path = Some(bytes_to_path(percent_decode(value, true), false)),
+            "line" => {
+                let value = String::from_utf8_lossy(&percent_decode(value, true)).into_owned();
+                match value.parse::<usize>() {
+                    // Lines are one-based in links, zero-based internally.
+                    Ok(n) if n > 0 => line = Some(n - 1),
+                    _ => return Err(UrlError::InvalidLine(value)),
+                }
+            }
+            // Unknown parameters are ignored, for forward compatibility.
+            _ => {}
+        }
+    }
+    let path = path
+        .filter(|path| !path.as_os_str().is_empty())
+        .ok_or(UrlError::MissingPath)?;
+    Ok(OpenRequest { path, line })
+}
+
+/// Decode `%XX` escapes. Invalid or truncated escapes are kept as is; with
+/// `plus_as_space` (query strings), `+` decodes to a space.
+fn percent_decode(input: &str, plus_as_space: bool) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = |offset: usize| {
+                    bytes
+                        .get(i + offset)
+                        .and_then(|&b| (b as char).to_digit(16))
+                };
+                match (hex(1), hex(2)) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 3;
+                        continue;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b'+' if plus_as_space => decoded.push(b' '),
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    decoded
+}
+
+/// The path named by a relative link target such as `my%20notes.md`.
+pub fn decode_path(target: &str) -> PathBuf {
+    bytes_to_path(percent_decode(target, false), false)
+}
+
+/// Percent-encode `input` for use as a query parameter value.
+fn percent_encode(input: &[u8]) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for &b in input {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~' | b'/') {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    encoded
+}
+
+/// Build a path from decoded bytes. Non-UTF-8 bytes are kept on Unix, where
+/// paths are arbitrary bytes, and replaced elsewhere. `from_file_url` drops
+/// the slash before a Windows drive letter (`/C:/notes.md`).
+fn bytes_to_path(bytes: Vec<u8>, from_file_url: bool) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt as _;
+        let _ = from_file_url;
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        let path = String::from_utf8_lossy(&bytes).into_owned();
+        let is_drive = |s: &str| {
+            let s = s.as_bytes();
+            s.len() >= 3 && s[0] == b'/' && s[1].is_ascii_alphabetic() && s[2] == b':'
+        };
+        if from_file_url && is_drive(&path) {
+            PathBuf::from(&path[1..])
+        } else {
+            PathBuf::from(path)
+        }
+    }
+}
+
+fn path_bytes(path: &Path) -> Vec<u8> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt as _;
+        path.as_os_str().as_bytes().to_vec()
+    }
+    #[cfg(not(unix))]
+    {
+        path.to_string_lossy().into_owned().into_bytes()
+    }
+}
+
+/// The `markzap://open` link for `request`, the inverse of `parse_open_url`.
+pub fn deep_link(request: &OpenRequest) -> String {
+    let mut link = format!(
+        "{}://open?path={}",
+        SCHEME,
+        percent_encode(&path_bytes(&request.path))
+    );
+    if let Some(line) = request.line {
+        link.push_str(&format!("&line={}", line + 1));
+    }
+    link
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str, line: Option<usize>) -> OpenRequest {
+        OpenRequest {
+            path: PathBuf::from(path),
+            line,
+        }
+    }
+
+    #[test]
+    fn decodes_escapes() {
+        assert_eq!(percent_decode("my%20notes%2Emd", false), b"my notes.md");
+        assert_eq!(percent_decode("%c3%A9t%C3%A9", false), "été".as_bytes());
+    }
+
+    #[test]
+    fn keeps_invalid_and_truncated_escapes() {
+        assert_eq!(percent_decode("100%", false), b"100%");
+        assert_eq!(percent_decode("%zz%4", false), b"%zz%4");
+        assert_eq!(percent_decode("%4g", false), b"%4g");
+    }
+
+    #[test]
+    fn plus_is_a_space_only_in_queries() {
+        assert_eq!(percent_decode("a+b", true), b"a b");
+        assert_eq!(percent_decode("a+b", false), b"a+b");
+        assert_eq!(percent_decode("a%2Bb", true), b"a+b");
+    }
+
+    #[test]
+    fn keeps_non_utf8_bytes() {
+        assert_eq!(percent_decode("%FF%FE", false), vec![0xFF, 0xFE]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path_survives() {
+        use std::os::unix::ffi::OsStrExt as _;
+        let path = file_url_to_path("///tmp/%FF.md").unwrap();
+        assert_eq!(path.as_os_str().as_bytes(), b"/tmp/\xFF.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_urls() {
+        assert_eq!(
+            file_url_to_path("///home/a/my%20notes.md"),
+            Ok(PathBuf::from("/home/a/my notes.md"))
+        );
+        assert_eq!(
+            file_url_to_path("//localhost/home/a.md?x=1#top"),
+            Ok(PathBuf::from("/home/a.md"))
+        );
+        assert_eq!(
+            file_url_to_path("//LOCALHOST/a+b.md"),
+            Ok(PathBuf::from("/a+b.md"))
+        );
+        assert_eq!(
+            file_url_to_path("/home/a.md"),
+            Ok(PathBuf::from("/home/a.md"))
+        );
+        assert_eq!(
+            file_url_to_path("//server/share/a.md"),
+            Err(UrlError::RemoteHost("server".into()))
+        );
+    }
+
+    #[test]
+    fn other_schemes_are_unsupported() {
+        assert_eq!(
+            parse_open_url("https://example.com/a.md"),
+            Err(UrlError::UnsupportedScheme("https".into()))
+        );
+        assert_eq!(
+            parse_open_url("notes.md"),
+            Err(UrlError::UnsupportedScheme(String::new()))
+        );
+        assert_eq!(
+            parse_open_url("markzap://edit?path=/a.md"),
+            Err(UrlError::UnsupportedAction("edit".into()))
+        );
+    }
+
+    #[test]
+    fn deep_link_lines_are_one_based() {
+        assert_eq!(
+            parse_open_url("markzap://open?path=/a%20b.md&line=42"),
+            Ok(request("/a b.md", Some(41)))
+        );
+        assert_eq!(
+            parse_open_url("MarkZap://open/?line=1&path=/a+b.md&mode=edit"),
+            Ok(request("/a b.md", Some(0)))
+        );
+    }
+
+    #[test]
+    fn deep_link_errors() {
+        for line in ["0", "-1", "x", ""] {
+            assert_eq!(
+                parse_deep_link(&format!("//open?path=/a.md&line={}", line)),
+                Err(UrlError::InvalidLine(line.into()))
+            );
+        }
+        assert_eq!(parse_deep_link("//open?line=3"), Err(UrlError::MissingPath));
+        assert_eq!(parse_deep_link("//open?path="), Err(UrlError::MissingPath));
+    }
+
+    #[test]
+    fn deep_link_round_trip() {
+        for request in [
+            request("/home/a/notes.md", None),
+            request("/home/a/my notes & more+1.md", Some(9)),
+            request("/tmp/été?#%.md", Some(0)),
+        ] {
+            assert_eq!(parse_open_url(&deep_link(&request)), Ok(request));
+        }
+    }
+
+    #[test]
+    fn urls_and_paths() {
+        assert!(is_url("file:///a.md"));
+        assert!(is_url("markzap://open?path=/a.md"));
+        assert!(!is_url("C:\\notes.md"));
+        assert!(!is_url("notes.md"));
+    }
+}
@@ -0,0 +1,149 @@
+use gpui::*;
+
+use crate::appearance::Appearance;
+use crate::i18n::{self, Language};
+use crate::settings::Settings;
+use crate::themes;
+use crate::views::picker::PickerItem;
+use crate::{
+    AddTableColumn, AddTableRow, CheckLinks, ClearRecentFiles, CloseTab, CycleHeading, ExportHtml,
+    Find, FormatDocument, GoToHeading, InsertLink, NextTab, OpenFile, OpenSettings, PreviousTab,
+    QuickOpen, Quit, RemoveTableColumn, RemoveTableRow, ResetZoom, RestoreSession, SearchInFolder,
+    SelectAppearance, SelectLanguage, SelectPreviewTheme, SortTable, StartPresentation, ToggleBold,
+    ToggleCode, ToggleCodeBlock, ToggleItalic, ToggleMode, ToggleProblems, ToggleQuote, ToggleTask,
+    ZoomIn, ZoomOut,
+};
+
+/// A palette entry: its label and the action it dispatches.
+pub struct Command {
+    pub label: SharedString,
+    pub action: Box<dyn Action>,
+}
+
+fn command(label: impl Into<SharedString>, action: impl Action) -> Command {
+    Command {
+        label: label.into(),
+        action: Box::new(action),
+    }
+}
+
+/// Every command available in a document window. `has_presentation` adds
+/// the Present command, like the presentation button.
+pub fn commands(has_presentation: bool, cx: &App) -> Vec<Command> {
+    let t = i18n::tr(cx);
+    let trim = |label: &'static str| label.trim_end_matches('\u{2026}');
+
+    let mut commands = vec![
+        command(t.menu_toggle_mode, ToggleMode),
+        command(trim(t.menu_find), Find),
+        command(trim(t.menu_go_to_heading), GoToHeading),
+        command(t.menu_problems, ToggleProblems),
+        command(t.menu_check_links, CheckLinks),
+        command(t.menu_format_document, FormatDocument),
+        command(t.menu_toggle_task, ToggleTask),
+        command(format!("{}: {}", t.menu_format, t.menu_bold), ToggleBold),
+        command(
+            format!("{}: {}", t.menu_format, t.menu_italic),
+            ToggleItalic,
+        ),
+        command(format!("{}: {}", t.menu_format, t.menu_code), ToggleCode),
+        command(format!("{}: {}", t.menu_format, t.menu_link), InsertLink),
+        command(
+            format!("{}: {}", t.menu_format, t.menu_heading),
+            CycleHeading,
+        ),
+        command(format!("{}: {}", t.menu_format, t.menu_quote), ToggleQuote),
+        command(
+            format!("{}: {}", t.menu_format, t.menu_code_block),
+            ToggleCodeBlock,
+        ),
+        command(format!("{}: {}", t.menu_table, t.menu_add_row), AddTableRow),
+        command(
+            format!("{}: {}", t.menu_table, t.menu_remove_row),
+            RemoveTableRow,
+        ),
+        command(
+            format!("{}: {}", t.menu_table, t.menu_add_column),
+            AddTableColumn,
+        ),
+        command(
+            format!("{}: {}", t.menu_table, t.menu_remove_column),
+            RemoveTableColumn,
+        ),
+        command(
+            format!("{}: {}", t.menu_table, t.menu_sort_table),
+            SortTable,
+        ),
+        command(trim(t.menu_quick_open), QuickOpen),
+        command(trim(t.menu_search_in_folder), SearchInFolder),
+        command(trim(t.menu_open), OpenFile),
+        command(t.menu_reopen_session, RestoreSession),
+        command(t.menu_clear_recent, ClearRecentFiles),
+        command(trim(t.menu_export_html), ExportHtml),
+        command(t.menu_zoom_in, ZoomIn),
+        command(t.menu_zoom_out, ZoomOut),
+        command(t.menu_reset_zoom, ResetZoom),
+        command(trim(t.menu_settings), OpenSettings),
+    ];
+    if has_presentation {
+        commands.insert(1, command(t.menu_present, StartPresentation));
+    }
+    if Settings::get(cx).open_in_tabs {
+        commands.extend([
+            command(t.menu_next_tab, NextTab),
+            command(t.menu_previous_tab, PreviousTab),
+            command(t.menu_close_tab, CloseTab),
+        ]);
+    } else {
+        commands.push(command(t.menu_close_window, CloseTab));
+    }
+    commands.extend(Appearance::ALL.into_iter().map(|appearance| {
+        command(
+            format!("{}: {}", t.menu_appearance, appearance.label(t)),
+            SelectAppearance { appearance },
+        )
+    }));
+    commands.extend(themes::available_themes().into_iter().map(|(id, label)| {
+        command(
+            format!("{}: {}", t.menu_preview_theme, label),
+            SelectPreviewTheme { id },
+        )
+    }));
+    commands.push(command(
+        format!("{}: {}", t.menu_language, t.language_auto),
+        SelectLanguage {
+            tag: "auto".to_string(),
+        },
+    ));
+    commands.extend(Language::ALL.into_iter().map(|language| {
+        command(
+            format!("{}: {}", t.menu_language, language.native_name()),
+            SelectLanguage {
+                tag: language.tag().to_string(),
+            },
+        )
+    }));
+    commands.push(command(t.menu_quit, Quit));
+    commands
+}
+
+/// Picker entries for `commands`, showing the key binding of each.
+pub fn items(commands: &[Command], window: &Window) -> Vec<PickerItem> {
+    commands
+        .iter()
+        .map(|command| PickerItem {
+            label: command.label.clone(),
+            detail: window
+                .highest_precedence_binding_for_action(command.action.as_ref())
+                .map(|binding| {
+                    binding
+                        .keystrokes()
+                        .iter()
+                        .map(|keystroke| keystroke.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .into()
+                }),
+        })
+        .collect()
+}
@@ -0,0 +1,201 @@
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::{ActiveTheme as _, IconName, Selectable as _, Sizable as _};
+use gpui_component::{h_flex, v_flex};
+
+use crate::find::FindOptions;
+use crate::i18n;
+
+/// Requests from the find panel, carried out by the view owning the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindPanelEvent {
+    /// The query or one of the options changed: search again.
+    Search,
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+    Close,
+}
+
+/// Native find/replace bar shown above the editor in Edit mode.
+pub struct FindPanel {
+    query: Entity<InputState>,
+    replacement: Entity<InputState>,
+    options: FindOptions,
+    /// Match count ("3 of 17") or error, set by the owner after each search.
+    status: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<FindPanelEvent> for FindPanel {}
+
+impl FindPanel {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let t = i18n::tr(cx);
+        let query = cx.new(|cx| InputState::new(window, cx).placeholder(t.find_placeholder));
+        let replacement =
+            cx.new(|cx| InputState::new(window, cx).placeholder(t.replace_placeholder));
+
+        let subscriptions = vec![
+            cx.subscribe(&query, |_, _, event: &InputEvent, cx| match event {
+                InputEvent::Change => cx.emit(FindPanelEvent::Search),
+                InputEvent::PressEnter { secondary: false } => cx.emit(FindPanelEvent::Next),
+                InputEvent::PressEnter { secondary: true } => cx.emit(FindPanelEvent::Previous),
+                _ => {}
+            }),
+            cx.subscribe(&replacement, |_, _, event: &InputEvent, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    cx.emit(FindPanelEvent::Replace);
+                }
+            }),
+        ];
+
+        Self {
+            query,
+            replacement,
+            options: FindOptions::default(),
+            status: SharedString::default(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn query(&self, cx: &App) -> String {
+        self.query.read(cx).value().to_string()
+    }
+
+    pub fn replacement(&self, cx: &App) -> String {
+        self.replacement.read(cx).value().to_string()
+    }
+
+    pub fn options(&self) -> FindOptions {
+        self.options
+    }
+
+    pub fn set_status(&mut self, status: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.status = status.into();
+        cx.notify();
+    }
+
+    /// Move keyboard focus to the query field.
+    pub fn focus(&self, window: &mut Window, cx: &App) {
+        window.focus(&self.query.focus_handle(cx));
+    }
+
+    fn toggle(&mut self, update: impl FnOnce(&mut FindOptions), cx: &mut Context<Self>) {
+        update(&mut self.options);
+        cx.emit(FindPanelEvent::Search);
+        cx.notify();
+    }
+}
+
+impl Render for FindPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let t = i18n::tr(cx);
+        let options = self.options;
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .px_4()
+            .py_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .on_key_down(cx.listener(|_, event: &KeyDownEvent, _, cx| {
+                if event.keystroke.key == "escape" {
+                    cx.emit(FindPanelEvent::Close);
+                }
+            }))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().child(Input::new(&self.query).small()))
+                    .child(
+                        Button::new("find-case")
+                            .label("Aa")
+                            .small()
+                            .ghost()
+                            .tooltip(t.find_case_sensitive)
+                            .selected(options.case_sensitive)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.toggle(|o| o.case_sensitive = !o.case_sensitive, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("find-word")
+                            .label("W")
+                            .small()
+                            .ghost()
+                            .tooltip(t.find_whole_word)
+                            .selected(options.whole_word)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.toggle(|o| o.whole_word = !o.whole_word, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("find-regex")
+                            .label(".*")
+                            .small()
+                            .ghost()
+                            .tooltip(t.find_regex)
+                            .selected(options.regex)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.toggle(|o| o.regex = !o.regex, cx)
+                            })),
+                    )
+                    .child(
+                        div()
+                            .min_w(px(80.))
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(self.status.clone()),
+                    )
+                    .child(
+                        Button::new("find-prev")
+                            .icon(IconName::ChevronUp)
+                            .small()
+                            .ghost()
+                            .tooltip(t.find_previous)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(FindPanelEvent::Previous))),
+                    )
+                    .child(
+                        Button::new("find-next")
+                            .icon(IconName::ChevronDown)
+                            .small()
+                            .ghost()
+                            .tooltip(t.find_next)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(FindPanelEvent::Next))),
+                    )
+                    .child(
+                        Button::new("find-close")
+                            .icon(IconName::Close)
+                            .small()
+                            .ghost()
+                            .tooltip(t.find_close)
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(FindPanelEvent::Close))),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().child(Input::new(&self.replacement).small()))
+                    .child(
+                        Button::new("replace-one")
+                            .label(t.replace)
+                            .small()
+                            .on_click(cx.listener(|_, _, _, cx| cx.emit(FindPanelEvent::Replace))),
+                    )
+                    .child(
+                        Button::new("replace-all")
+                            .label(t.replace_all)
+                            .small()
+                            .on_click(
+                                cx.listener(|_, _, _, cx| cx.emit(FindPanelEvent::ReplaceAll)),
+                            ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,52 @@
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::menu::{DropdownMenu as _, PopupMenu};
+use gpui_component::{ActiveTheme as _, Sizable as _, h_flex};
+
+use crate::menus;
+
+/// Fill `menu` with the items of the menu at `path` (see `menus::items_at`).
+fn fill(
+    mut menu: PopupMenu,
+    path: Vec<usize>,
+    window: &mut Window,
+    cx: &mut Context<PopupMenu>,
+) -> PopupMenu {
+    for (ix, item) in menus::items_at(&path, cx).into_iter().enumerate() {
+        menu = match item {
+            MenuItem::Separator => menu.separator(),
+            MenuItem::Action { name, action, .. } => menu.menu(name, action),
+            MenuItem::Submenu(submenu) => {
+                let mut path = path.clone();
+                path.push(ix);
+                menu.submenu(submenu.name, window, cx, move |menu, window, cx| {
+                    fill(menu, path.clone(), window, cx)
+                })
+            }
+            _ => menu,
+        };
+    }
+    menu
+}
+
+/// The application menus drawn at the top of a document window, for
+/// platforms where `cx.set_menus` shows nothing (Linux, Windows).
+///
+/// Menus are rebuilt each time one opens, so they always reflect the
+/// current recent files, theme and language.
+pub fn menu_bar(cx: &App) -> impl IntoElement {
+    let names: Vec<SharedString> = menus::build(cx).into_iter().map(|menu| menu.name).collect();
+    h_flex()
+        .w_full()
+        .px_2()
+        .gap_1()
+        .border_b_1()
+        .border_color(cx.theme().border)
+        .children(names.into_iter().enumerate().map(|(ix, name)| {
+            Button::new(("menu-bar", ix))
+                .label(name)
+                .ghost()
+                .xsmall()
+                .dropdown_menu(move |menu, window, cx| fill(menu, vec![ix], window, cx))
+        }))
+}
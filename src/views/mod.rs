@@ -1 +1,9 @@
+pub mod command_palette;
+pub mod find_panel;
+pub mod menu_bar;
+pub mod picker;
 pub mod presentation;
+pub mod quick_open;
+pub mod search;
+pub mod settings;
+pub mod workspace;
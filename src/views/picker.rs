@@ -0,0 +1,205 @@
+use std::ops::Range;
+
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::ActiveTheme as _;
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::{h_flex, v_flex};
+
+use crate::fuzzy;
+
+const MAX_VISIBLE: usize = 50;
+
+/// One entry of a picker.
+#[derive(Debug, Clone)]
+pub struct PickerItem {
+    /// Text matched against the query.
+    pub label: SharedString,
+    /// Secondary text shown dimmed on the right (a folder, a key binding).
+    pub detail: Option<SharedString>,
+}
+
+/// Outcome of a picker, handled by the view that opened it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerEvent {
+    /// The item at this index of the original list was chosen.
+    Confirm(usize),
+    Dismiss,
+}
+
+/// Fuzzy-filtered list with a query field, shown as an overlay over a
+/// document window (quick open, command palette).
+pub struct Picker {
+    query: Entity<InputState>,
+    items: Vec<PickerItem>,
+    /// Indices into `items` of the visible matches, best first, with the
+    /// byte offsets of the matched characters in their label.
+    matches: Vec<(usize, Vec<usize>)>,
+    selected: usize,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<PickerEvent> for Picker {}
+
+impl Picker {
+    pub fn new(
+        items: Vec<PickerItem>,
+        placeholder: &'static str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query = cx.new(|cx| InputState::new(window, cx).placeholder(placeholder));
+        let subscription = cx.subscribe(&query, |this, _, event: &InputEvent, cx| match event {
+            InputEvent::Change => this.filter(cx),
+            InputEvent::PressEnter { .. } => this.confirm(cx),
+            _ => {}
+        });
+        let mut picker = Self {
+            query,
+            items,
+            matches: Vec::new(),
+            selected: 0,
+            _subscription: subscription,
+        };
+        picker.filter(cx);
+        picker
+    }
+
+    /// Replace the items, e.g. once a background scan completes.
+    pub fn set_items(&mut self, items: Vec<PickerItem>, cx: &mut Context<Self>) {
+        self.items = items;
+        self.filter(cx);
+    }
+
+    pub fn focus(&self, window: &mut Window, cx: &App) {
+        window.focus(&self.query.focus_handle(cx));
+    }
+
+    fn filter(&mut self, cx: &mut Context<Self>) {
+        let query = self.query.read(cx).value().to_string();
+        if query.trim().is_empty() {
+            self.matches = (0..self.items.len().min(MAX_VISIBLE))
+                .map(|ix| (ix, Vec::new()))
+                .collect();
+        } else {
+            let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+                .items
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, item)| {
+                    fuzzy::fuzzy_match(&query, &item.label).map(|m| (m.score, ix, m.positions))
+                })
+                .collect();
+            // Stable on ties, so the original order (e.g. recency) breaks them.
+            scored.sort_by(|a, b| b.0.cmp(&a.0));
+            scored.truncate(MAX_VISIBLE);
+            self.matches = scored
+                .into_iter()
+                .map(|(_, ix, positions)| (ix, positions))
+                .collect();
+        }
+        self.selected = 0;
+        cx.notify();
+    }
+
+    fn confirm(&mut self, cx: &mut Context<Self>) {
+        if let Some(&(ix, _)) = self.matches.get(self.selected) {
+            cx.emit(PickerEvent::Confirm(ix));
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize, cx: &mut Context<Self>) {
+        let count = self.matches.len() as isize;
+        if count > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(count) as usize;
+            cx.notify();
+        }
+    }
+
+    fn render_item(&self, row: usize, cx: &Context<Self>) -> impl IntoElement {
+        let (ix, ref positions) = self.matches[row];
+        let item = &self.items[ix];
+        let highlights: Vec<(Range<usize>, HighlightStyle)> = positions
+            .iter()
+            .map(|&offset| {
+                let len = item.label[offset..]
+                    .chars()
+                    .next()
+                    .map_or(1, char::len_utf8);
+                (
+                    offset..offset + len,
+                    HighlightStyle {
+                        font_weight: Some(FontWeight::BOLD),
+                        color: Some(cx.theme().primary),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        let selected = row == self.selected;
+
+        h_flex()
+            .id(("picker-item", row))
+            .w_full()
+            .px_3()
+            .py_1()
+            .gap_4()
+            .justify_between()
+            .rounded_md()
+            .cursor_pointer()
+            .when(selected, |this| this.bg(cx.theme().accent))
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.selected = row;
+                this.confirm(cx);
+            }))
+            .child(
+                div()
+                    .text_sm()
+                    .truncate()
+                    .child(StyledText::new(item.label.clone()).with_highlights(highlights)),
+            )
+            .children(item.detail.clone().map(|detail| {
+                div()
+                    .text_xs()
+                    .flex_shrink_0()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(detail)
+            }))
+    }
+}
+
+impl Render for Picker {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows: Vec<AnyElement> = (0..self.matches.len())
+            .map(|row| self.render_item(row, cx).into_any_element())
+            .collect();
+
+        v_flex()
+            .w(px(560.))
+            .max_h(px(420.))
+            .p_2()
+            .gap_2()
+            .bg(cx.theme().background)
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_lg()
+            .shadow_lg()
+            .capture_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                match event.keystroke.key.as_str() {
+                    "up" => this.move_selection(-1, cx),
+                    "down" => this.move_selection(1, cx),
+                    "escape" => cx.emit(PickerEvent::Dismiss),
+                    _ => return,
+                }
+                cx.stop_propagation();
+            }))
+            .child(Input::new(&self.query))
+            .child(
+                div()
+                    .id("picker-items")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .child(v_flex().children(rows)),
+            )
+    }
+}
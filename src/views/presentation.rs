@@ -1,6 +1,26 @@
 use gpui::*;
-use gpui_component::Root;
 use gpui_component::webview::WebView;
+use gpui_component::{ActiveTheme as _, Root};
+
+use crate::i18n;
+use crate::slidev;
+
+/// Presentation WebViews currently open, re-themed when the appearance changes.
+#[derive(Default)]
+struct OpenPresentations(Vec<WeakEntity<WebView>>);
+
+impl Global for OpenPresentations {}
+
+/// Apply the current light/dark appearance to every open presentation.
+pub fn retheme_presentations(cx: &mut App) {
+    let script = slidev::retheme_script(cx.theme().is_dark());
+    let presentations = &mut cx.default_global::<OpenPresentations>().0;
+    presentations.retain(|webview| webview.upgrade().is_some());
+    let webviews: Vec<Entity<WebView>> = presentations.iter().filter_map(|w| w.upgrade()).collect();
+    for webview in webviews {
+        webview.read(cx).evaluate_script(&script).ok();
+    }
+}
 
 /// Opens a new window containing a WebView that renders the presentation HTML.
 /// The window is sized to 2/3 of the primary display.
@@ -16,7 +36,7 @@ pub fn open_presentation_window(html: String, cx: &mut App) {
     if let Ok(window_handle) = cx.open_window(
         WindowOptions {
             titlebar: Some(TitlebarOptions {
-                title: Some("MarkZap Presentation".into()),
+                title: Some(i18n::tr(cx).presentation_title.into()),
                 ..Default::default()
             }),
             window_bounds: Some(WindowBounds::Fullscreen(Bounds::centered(
@@ -34,6 +54,9 @@ pub fn open_presentation_window(html: String, cx: &mut App) {
                 .expect("Failed to create WebView");
 
             let webview_entity = cx.new(|cx| WebView::new(wry_webview, window, cx));
+            cx.default_global::<OpenPresentations>()
+                .0
+                .push(webview_entity.downgrade());
 
             cx.new(|cx| Root::new(webview_entity, window, cx))
         },
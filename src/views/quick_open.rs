@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use gpui::*;
+
+use crate::session::RecentFiles;
+use crate::views::picker::PickerItem;
+
+/// Recently opened files, newest first, except `current`.
+pub fn recent_files(current: Option<&Path>, cx: &App) -> Vec<PathBuf> {
+    cx.try_global::<RecentFiles>()
+        .map(|recent| recent.paths())
+        .unwrap_or_default()
+        .iter()
+        .filter(|path| Some(path.as_path()) != current && path.exists())
+        .cloned()
+        .collect()
+}
+
+/// Append the `extra` paths not already in `paths`.
+pub fn merge(paths: &mut Vec<PathBuf>, extra: Vec<PathBuf>, current: Option<&Path>) {
+    let mut seen: HashSet<PathBuf> = paths.iter().cloned().collect();
+    for path in extra {
+        if Some(path.as_path()) != current && seen.insert(path.clone()) {
+            paths.push(path);
+        }
+    }
+}
+
+/// Picker entries for `paths`: the path relative to `folder` when inside
+/// it, otherwise the file name with its directory as detail.
+pub fn items(paths: &[PathBuf], folder: Option<&Path>) -> Vec<PickerItem> {
+    paths
+        .iter()
+        .map(|path| {
+            if let Some(relative) = folder.and_then(|folder| path.strip_prefix(folder).ok()) {
+                return PickerItem {
+                    label: relative.display().to_string().into(),
+                    detail: None,
+                };
+            }
+            PickerItem {
+                label: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string())
+                    .into(),
+                detail: path.parent().map(|dir| dir.display().to_string().into()),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,251 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use gpui::*;
+use gpui_component::button::Button;
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::{ActiveTheme as _, Root};
+use gpui_component::{h_flex, v_flex};
+
+use crate::i18n;
+use crate::open_requests::{self, OpenRequest};
+use crate::search::{SearchHit, SearchIndex};
+
+/// Window searching the Markdown files under a folder.
+pub struct SearchView {
+    root: Option<PathBuf>,
+    /// `None` while the folder is being indexed.
+    index: Option<Arc<SearchIndex>>,
+    query: Entity<InputState>,
+    hits: Vec<SearchHit>,
+    _subscription: Subscription,
+}
+
+impl SearchView {
+    fn new(root: Option<PathBuf>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let placeholder = i18n::tr(cx).search_placeholder;
+        let query = cx.new(|cx| InputState::new(window, cx).placeholder(placeholder));
+        let subscription = cx.subscribe(&query, |this, _, event: &InputEvent, cx| match event {
+            InputEvent::Change => this.search(cx),
+            InputEvent::PressEnter { .. } => {
+                if let Some(hit) = this.hits.first() {
+                    open_hit(hit, cx);
+                }
+            }
+            _ => {}
+        });
+        window.focus(&query.focus_handle(cx));
+
+        let mut view = Self {
+            root: None,
+            index: None,
+            query,
+            hits: Vec::new(),
+            _subscription: subscription,
+        };
+        if let Some(root) = root {
+            view.set_root(root, cx);
+        }
+        view
+    }
+
+    /// Index `root` in the background, then rerun the current query.
+    fn set_root(&mut self, root: PathBuf, cx: &mut Context<Self>) {
+        self.root = Some(root.clone());
+        self.index = None;
+        self.hits.clear();
+        cx.spawn(async move |this, cx| {
+            let index = cx
+                .background_spawn(async move { SearchIndex::build(&root) })
+                .await;
+            this.update(cx, |this, cx| {
+                // Ignore a stale index if another folder was chosen meanwhile.
+                if this.root.as_deref() == Some(index.root()) {
+                    this.index = Some(Arc::new(index));
+                    this.search(cx);
+                }
+            })
+            .ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn choose_folder(&mut self, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: None,
+        });
+        cx.spawn(async move |this, cx| {
+            if let Ok(Ok(Some(paths))) = receiver.await {
+                if let Some(root) = paths.into_iter().next() {
+                    this.update(cx, |this, cx| this.set_root(root, cx)).ok();
+                }
+            }
+        })
+        .detach();
+    }
+
+    fn search(&mut self, cx: &mut Context<Self>) {
+        let query = self.query.read(cx).value().to_string();
+        self.hits = match self.index {
+            Some(ref index) => index.search(&query),
+            None => Vec::new(),
+        };
+        cx.notify();
+    }
+
+    fn status(&self, cx: &App) -> String {
+        let t = i18n::tr(cx);
+        match (&self.root, &self.index) {
+            (None, _) => t.search_no_folder.to_string(),
+            (Some(_), None) => t.search_indexing.to_string(),
+            (Some(_), Some(index)) => {
+                if self.hits.is_empty() && !self.query.read(cx).value().trim().is_empty() {
+                    t.find_no_results.to_string()
+                } else {
+                    format!("{} {}", index.file_count(), t.search_files_indexed)
+                }
+            }
+        }
+    }
+
+    fn render_hit(&self, ix: usize, hit: &SearchHit, cx: &Context<Self>) -> impl IntoElement {
+        let relative = self
+            .root
+            .as_deref()
+            .and_then(|root| hit.path.strip_prefix(root).ok())
+            .unwrap_or(&hit.path)
+            .display()
+            .to_string();
+        let hover = cx.theme().accent;
+        let target = hit.clone();
+
+        v_flex()
+            .id(("search-hit", ix))
+            .w_full()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .cursor_pointer()
+            .hover(move |style| style.bg(hover))
+            .on_click(cx.listener(move |_, _, _, cx| open_hit(&target, cx)))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child(relative),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format!(":{}", hit.line + 1)),
+                    ),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(hit.snippet.clone()),
+            )
+    }
+}
+
+/// Open the document of `hit` at the matching line.
+fn open_hit(hit: &SearchHit, cx: &App) {
+    open_requests::send(
+        OpenRequest {
+            path: hit.path.clone(),
+            line: Some(hit.line),
+        },
+        cx,
+    );
+}
+
+impl Render for SearchView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let t = i18n::tr(cx);
+        let root_label = match self.root {
+            Some(ref root) => root.display().to_string(),
+            None => t.search_no_folder.to_string(),
+        };
+        let hits: Vec<AnyElement> = self
+            .hits
+            .iter()
+            .enumerate()
+            .map(|(ix, hit)| self.render_hit(ix, hit, cx).into_any_element())
+            .collect();
+
+        v_flex()
+            .size_full()
+            .p_4()
+            .gap_3()
+            .child(
+                h_flex()
+                    .gap_4()
+                    .items_center()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .truncate()
+                            .child(root_label),
+                    )
+                    .child(
+                        Button::new("choose-folder")
+                            .label(t.search_choose_folder)
+                            .on_click(cx.listener(|this, _, _, cx| this.choose_folder(cx))),
+                    ),
+            )
+            .child(Input::new(&self.query))
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(self.status(cx)),
+            )
+            .child(
+                div()
+                    .id("search-results")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .child(v_flex().gap_1().children(hits)),
+            )
+    }
+}
+
+/// Opens a folder search window, indexing `root` right away if given.
+pub fn open_search_window(root: Option<PathBuf>, cx: &mut App) {
+    if let Ok(window_handle) = cx.open_window(
+        WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some(i18n::tr(cx).search_title.into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(640.), px(560.)),
+                cx,
+            ))),
+            focus: true,
+            ..Default::default()
+        },
+        |window, cx| {
+            let view = cx.new(|cx| SearchView::new(root, window, cx));
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    ) {
+        window_handle
+            .update(cx, |_, window, _| {
+                window.activate_window();
+            })
+            .ok();
+    }
+}
@@ -0,0 +1,201 @@
+use gpui::*;
+use gpui_component::button::Button;
+use gpui_component::input::{Input, InputState};
+use gpui_component::switch::Switch;
+use gpui_component::{ActiveTheme as _, Root};
+use gpui_component::{h_flex, v_flex};
+
+use crate::i18n;
+use crate::settings::Settings;
+
+/// Form editing the values of `settings.toml`.
+pub struct SettingsView {
+    autosave_delay: Entity<InputState>,
+    window_width: Entity<InputState>,
+    editor_font: Entity<InputState>,
+    content_width: Entity<InputState>,
+    restore_session: bool,
+    open_in_tabs: bool,
+    format_toolbar: bool,
+    error: Option<SharedString>,
+}
+
+impl SettingsView {
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let settings = Settings::get(cx).clone();
+        let mut field =
+            |value: String| cx.new(|cx| InputState::new(window, cx).default_value(value));
+        Self {
+            autosave_delay: field(settings.autosave_delay_ms.to_string()),
+            window_width: field(settings.window_width.to_string()),
+            editor_font: field(settings.editor_font_family.clone()),
+            content_width: field(settings.content_width.to_string()),
+            restore_session: settings.restore_session,
+            open_in_tabs: settings.open_in_tabs,
+            format_toolbar: settings.format_toolbar,
+            error: None,
+        }
+    }
+
+    /// Build settings from the form, or describe the first invalid field.
+    fn read_form(&self, cx: &App) -> Result<Settings, &'static str> {
+        let t = i18n::tr(cx);
+        let value = |state: &Entity<InputState>| state.read(cx).value().trim().to_string();
+        let autosave_delay_ms = value(&self.autosave_delay)
+            .parse()
+            .map_err(|_| t.settings_invalid_autosave_delay)?;
+        let window_width = value(&self.window_width)
+            .parse()
+            .map_err(|_| t.settings_invalid_window_width)?;
+        let content_width = value(&self.content_width)
+            .parse()
+            .map_err(|_| t.settings_invalid_content_width)?;
+        let editor_font_family = value(&self.editor_font);
+        if editor_font_family.is_empty() {
+            return Err(t.settings_empty_editor_font);
+        }
+        Ok(Settings {
+            autosave_delay_ms,
+            window_width,
+            editor_font_family,
+            content_width,
+            restore_session: self.restore_session,
+            open_in_tabs: self.open_in_tabs,
+            format_toolbar: self.format_toolbar,
+            ..Settings::get(cx).clone()
+        })
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        self.error = match self.read_form(cx) {
+            Ok(settings) => match settings.save() {
+                Ok(()) => {
+                    // Apply right away; the file watcher sees no difference.
+                    cx.set_global(settings);
+                    None
+                }
+                Err(e) => Some(format!("{}: {}", i18n::tr(cx).settings_save_failed, e).into()),
+            },
+            Err(message) => Some(message.into()),
+        };
+        cx.notify();
+    }
+}
+
+fn row(label: &'static str, field: impl IntoElement) -> impl IntoElement {
+    h_flex()
+        .gap_4()
+        .items_center()
+        .child(div().w(px(180.)).child(label))
+        .child(div().flex_1().child(field))
+}
+
+impl Render for SettingsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let view = cx.entity().clone();
+        let t = i18n::tr(cx);
+        v_flex()
+            .size_full()
+            .p_4()
+            .gap_3()
+            .child(row(
+                t.settings_autosave_delay,
+                Input::new(&self.autosave_delay),
+            ))
+            .child(row(t.settings_window_width, Input::new(&self.window_width)))
+            .child(row(t.settings_editor_font, Input::new(&self.editor_font)))
+            .child(row(
+                t.settings_content_width,
+                Input::new(&self.content_width),
+            ))
+            .child(row(
+                t.settings_restore_session,
+                Switch::new("restore-session")
+                    .checked(self.restore_session)
+                    .on_click({
+                        let view = view.clone();
+                        move |checked, _window, cx| {
+                            view.update(cx, |this, cx| {
+                                this.restore_session = *checked;
+                                cx.notify();
+                            });
+                        }
+                    }),
+            ))
+            .child(row(
+                t.settings_open_in_tabs,
+                Switch::new("open-in-tabs")
+                    .checked(self.open_in_tabs)
+                    .on_click({
+                        let view = view.clone();
+                        move |checked, _window, cx| {
+                            view.update(cx, |this, cx| {
+                                this.open_in_tabs = *checked;
+                                cx.notify();
+                            });
+                        }
+                    }),
+            ))
+            .child(row(
+                t.settings_format_toolbar,
+                Switch::new("format-toolbar")
+                    .checked(self.format_toolbar)
+                    .on_click({
+                        let view = view.clone();
+                        move |checked, _window, cx| {
+                            view.update(cx, |this, cx| {
+                                this.format_toolbar = *checked;
+                                cx.notify();
+                            });
+                        }
+                    }),
+            ))
+            .child(
+                h_flex()
+                    .gap_4()
+                    .items_center()
+                    .justify_end()
+                    .children(
+                        self.error
+                            .clone()
+                            .map(|error| div().text_color(cx.theme().danger).child(error)),
+                    )
+                    .child(
+                        Button::new("save-settings")
+                            .label(t.settings_save)
+                            .on_click(move |_ev, _window, cx| {
+                                view.update(cx, |this, cx| this.save(cx));
+                            }),
+                    ),
+            )
+    }
+}
+
+/// Opens the settings window, centered on the primary display.
+pub fn open_settings_window(cx: &mut App) {
+    if let Ok(window_handle) = cx.open_window(
+        WindowOptions {
+            titlebar: Some(TitlebarOptions {
+                title: Some(i18n::tr(cx).settings_title.into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+                None,
+                size(px(560.), px(340.)),
+                cx,
+            ))),
+            focus: true,
+            ..Default::default()
+        },
+        |window, cx| {
+            let view = cx.new(|cx| SettingsView::new(window, cx));
+            cx.new(|cx| Root::new(view, window, cx))
+        },
+    ) {
+        window_handle
+            .update(cx, |_, window, _| {
+                window.activate_window();
+            })
+            .ok();
+    }
+}
@@ -0,0 +1,293 @@
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::ActiveTheme as _;
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::h_flex;
+use gpui_component::{IconName, Sizable as _};
+
+use crate::app::AppView;
+use crate::documents::{self, DocumentRegistry};
+use crate::{CloseTab, NextTab, PreviousTab};
+
+/// Tabbed document windows, most recently created last.
+#[derive(Default)]
+struct Workspaces(Vec<(AnyWindowHandle, WeakEntity<Workspace>)>);
+
+impl Global for Workspaces {}
+
+/// Hover group of a tab, for the controls shown only while it is hovered.
+const TAB_GROUP: &str = "tab";
+
+/// A tab being dragged to a new position in the tab strip.
+#[derive(Clone)]
+struct DraggedTab {
+    ix: usize,
+    label: SharedString,
+}
+
+impl Render for DraggedTab {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .px_3()
+            .py_1()
+            .rounded_md()
+            .text_sm()
+            .bg(cx.theme().secondary)
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(self.label.clone())
+    }
+}
+
+/// Window container showing several documents as tabs, used when the
+/// `open_in_tabs` setting is on.
+pub struct Workspace {
+    window: AnyWindowHandle,
+    tabs: Vec<Entity<AppView>>,
+    active: usize,
+    _tab_subscriptions: Vec<Subscription>,
+}
+
+impl Workspace {
+    pub fn new(view: Entity<AppView>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let handle = window.window_handle();
+        cx.default_global::<Workspaces>()
+            .0
+            .push((handle, cx.weak_entity()));
+        // Dirty indicators and titles follow the documents.
+        let subscription = cx.observe(&view, |_, _, cx| cx.notify());
+        Self {
+            window: handle,
+            tabs: vec![view],
+            active: 0,
+            _tab_subscriptions: vec![subscription],
+        }
+    }
+
+    /// Add `view` as a new tab after the active one and show it.
+    pub fn add_tab(&mut self, view: Entity<AppView>, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = (self.active + 1).min(self.tabs.len());
+        self._tab_subscriptions
+            .insert(ix, cx.observe(&view, |_, _, cx| cx.notify()));
+        self.tabs.insert(ix, view);
+        self.activate(ix, window, cx);
+    }
+
+    /// Show the tab at `ix`, hiding the native preview of the others.
+    pub fn activate(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix >= self.tabs.len() {
+            return;
+        }
+        self.active = ix;
+        for (i, tab) in self.tabs.iter().enumerate() {
+            tab.update(cx, |tab, cx| tab.set_visible(i == ix, cx));
+        }
+        let tab = self.tabs[ix].clone();
+        window.set_window_title(&tab.read(cx).title());
+        window.focus(&tab.focus_handle(cx));
+        cx.notify();
+    }
+
+    /// Close the tab at `ix` after flushing its edits; the last tab closes
+    /// the window.
+    fn close(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix >= self.tabs.len() {
+            return;
+        }
+        if self.tabs.len() == 1 {
+            documents::close_window(self.window, cx);
+            window.remove_window();
+            return;
+        }
+        let tab = self.tabs.remove(ix);
+        self._tab_subscriptions.remove(ix);
+        tab.update(cx, |tab, cx| {
+            tab.save_to_file();
+            tab.set_visible(false, cx);
+        });
+        DocumentRegistry::unregister_view(&tab, cx);
+        let active = if self.active > ix || self.active == self.tabs.len() {
+            self.active - 1
+        } else {
+            self.active
+        };
+        self.activate(active, window, cx);
+    }
+
+    /// Move the tab at `from` to position `to`, keeping it active if it was.
+    fn move_tab(&mut self, from: usize, to: usize, cx: &mut Context<Self>) {
+        if from == to || from >= self.tabs.len() || to >= self.tabs.len() {
+            return;
+        }
+        let active = self.tabs[self.active].clone();
+        let tab = self.tabs.remove(from);
+        let subscription = self._tab_subscriptions.remove(from);
+        self.tabs.insert(to, tab);
+        self._tab_subscriptions.insert(to, subscription);
+        self.active = self.tabs.iter().position(|t| *t == active).unwrap_or(0);
+        cx.notify();
+    }
+
+    fn close_tab(&mut self, _: &CloseTab, window: &mut Window, cx: &mut Context<Self>) {
+        self.close(self.active, window, cx);
+    }
+
+    fn next_tab(&mut self, _: &NextTab, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = (self.active + 1) % self.tabs.len();
+        self.activate(ix, window, cx);
+    }
+
+    fn previous_tab(&mut self, _: &PreviousTab, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = (self.active + self.tabs.len() - 1) % self.tabs.len();
+        self.activate(ix, window, cx);
+    }
+
+    fn render_tab(&self, ix: usize, cx: &Context<Self>) -> impl IntoElement {
+        let tab = self.tabs[ix].read(cx);
+        let label: SharedString = tab.tab_label().into();
+        let dirty = tab.is_dirty();
+        let is_active = ix == self.active;
+        let theme = cx.theme();
+        let (drop_bg, hover_bg) = (theme.drop_target, theme.accent);
+
+        h_flex()
+            .id(("tab", ix))
+            .group(TAB_GROUP)
+            .h_full()
+            .px_3()
+            .gap_2()
+            .items_center()
+            .text_sm()
+            .border_r_1()
+            .border_color(theme.border)
+            .cursor_pointer()
+            .when(is_active, |this| this.bg(theme.background))
+            .when(!is_active, |this| {
+                this.text_color(theme.muted_foreground)
+                    .hover(move |style| style.bg(hover_bg))
+            })
+            .on_click(cx.listener(move |this, _, window, cx| this.activate(ix, window, cx)))
+            .on_drag(
+                DraggedTab {
+                    ix,
+                    label: label.clone(),
+                },
+                |tab, _, _, cx| cx.new(|_| tab.clone()),
+            )
+            .drag_over::<DraggedTab>(move |style, _, _, _| style.bg(drop_bg))
+            .on_drop(cx.listener(move |this, dragged: &DraggedTab, _, cx| {
+                this.move_tab(dragged.ix, ix, cx)
+            }))
+            .child(label)
+            .child(
+                // Unsaved edits: a dot over the close button, which shows
+                // when the tab is hovered.
+                div()
+                    .relative()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(
+                        div()
+                            .when(dirty, |this| {
+                                this.invisible()
+                                    .group_hover(TAB_GROUP, |style| style.visible())
+                            })
+                            .child(
+                                Button::new(("close-tab", ix))
+                                    .icon(IconName::Close)
+                                    .ghost()
+                                    .xsmall()
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        cx.stop_propagation();
+                                        this.close(ix, window, cx);
+                                    })),
+                            ),
+                    )
+                    .when(dirty, |this| {
+                        this.child(
+                            div()
+                                .absolute()
+                                .size_full()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .group_hover(TAB_GROUP, |style| style.invisible())
+                                .child(
+                                    div().size(px(8.)).rounded_full().bg(theme.muted_foreground),
+                                ),
+                        )
+                    }),
+            )
+    }
+}
+
+impl Render for Workspace {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let tabs: Vec<AnyElement> = (0..self.tabs.len())
+            .map(|ix| self.render_tab(ix, cx).into_any_element())
+            .collect();
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .on_action(cx.listener(Self::close_tab))
+            .on_action(cx.listener(Self::next_tab))
+            .on_action(cx.listener(Self::previous_tab))
+            .child(
+                h_flex()
+                    .id("tab-strip")
+                    .w_full()
+                    .h(px(32.))
+                    .flex_none()
+                    .overflow_x_scroll()
+                    .bg(cx.theme().secondary)
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .children(tabs),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .child(self.tabs[self.active].clone()),
+            )
+    }
+}
+
+/// The tabbed window to open new documents in: the active window if it is
+/// one, else the most recently created.
+pub fn target(cx: &mut App) -> Option<(AnyWindowHandle, Entity<Workspace>)> {
+    let active = cx.active_window();
+    let workspaces = &mut cx.default_global::<Workspaces>().0;
+    workspaces.retain(|(_, workspace)| workspace.upgrade().is_some());
+    let (window, workspace) = workspaces
+        .iter()
+        .find(|(window, _)| Some(*window) == active)
+        .or_else(|| workspaces.last())?;
+    Some((*window, workspace.upgrade()?))
+}
+
+/// Activate the tab showing `view`, if its window is tabbed.
+pub fn reveal(window: AnyWindowHandle, view: &Entity<AppView>, cx: &mut App) {
+    let workspace = cx.try_global::<Workspaces>().and_then(|workspaces| {
+        workspaces
+            .0
+            .iter()
+            .find(|(w, _)| *w == window)
+            .and_then(|(_, workspace)| workspace.upgrade())
+    });
+    let Some(workspace) = workspace else {
+        return;
+    };
+    window
+        .update(cx, |_, window, cx| {
+            workspace.update(cx, |workspace, cx| {
+                if let Some(ix) = workspace.tabs.iter().position(|t| t == view) {
+                    workspace.activate(ix, window, cx);
+                }
+            });
+        })
+        .ok();
+}